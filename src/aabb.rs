@@ -0,0 +1,86 @@
+use glam::{Affine3A, Vec3};
+
+use crate::{consts::FAR_AWAY, ray::Ray};
+
+/// Axis-aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Default for Aabb {
+    fn default() -> Self {
+        Aabb::EMPTY
+    }
+}
+
+impl Aabb {
+    pub const EMPTY: Aabb = Aabb {
+        min: Vec3::INFINITY,
+        max: Vec3::NEG_INFINITY,
+    };
+
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Aabb { min, max }
+    }
+
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Self {
+        points.into_iter().fold(Aabb::EMPTY, Aabb::grow)
+    }
+
+    pub fn grow(self, point: Vec3) -> Self {
+        Aabb {
+            min: self.min.min(point),
+            max: self.max.max(point),
+        }
+    }
+
+    pub fn union(self, other: Aabb) -> Self {
+        Aabb {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// Whether the box has a finite size, unlike the bounds of planes and other unbounded objects,
+    /// which reach [`FAR_AWAY`].
+    pub fn is_bounded(&self) -> bool {
+        (self.max - self.min).max_element() < FAR_AWAY
+    }
+
+    pub fn centroid(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (a, b) = (self.min, self.max);
+        [
+            Vec3::new(a.x, a.y, a.z),
+            Vec3::new(b.x, a.y, a.z),
+            Vec3::new(a.x, b.y, a.z),
+            Vec3::new(b.x, b.y, a.z),
+            Vec3::new(a.x, a.y, b.z),
+            Vec3::new(b.x, a.y, b.z),
+            Vec3::new(a.x, b.y, b.z),
+            Vec3::new(b.x, b.y, b.z),
+        ]
+    }
+
+    /// Returns the bounds of this box after transforming it by `transform`.
+    pub fn transform(&self, transform: &Affine3A) -> Self {
+        Aabb::from_points(self.corners().map(|p| transform.transform_point3(p)))
+    }
+
+    /// Slab test against a ray, given the reciprocal of its direction. Returns the distance at
+    /// which the ray enters the box if it does so before `max_distance`.
+    pub fn intersect(&self, ray: &Ray, inv_direction: Vec3, max_distance: f32) -> Option<f32> {
+        let t1 = (self.min - ray.origin) * inv_direction;
+        let t2 = (self.max - ray.origin) * inv_direction;
+
+        let near = t1.min(t2).max_element().max(0.);
+        let far = t1.max(t2).min_element().min(max_distance);
+
+        (near <= far).then_some(near)
+    }
+}
@@ -0,0 +1,360 @@
+use std::ops::{Add, Mul};
+
+use glam::{Affine3A, EulerRot, Quat, Vec3};
+
+use crate::{
+    camera::CameraMotion,
+    color::Color,
+    material::Material,
+    object::{moving::Moving, Object},
+    object_store::{MaterialId, ObjectId, ObjectStore},
+};
+
+/// Values that can be blended by weighted sums, which is all that linear and Catmull–Rom
+/// interpolation need.
+pub trait Animatable: Copy + Add<Output = Self> + Mul<f32, Output = Self> {}
+
+impl<T: Copy + Add<Output = T> + Mul<f32, Output = T>> Animatable for T {}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Interpolation {
+    #[default]
+    Linear,
+    /// Catmull–Rom spline passing through every keyframe. The tangents are scaled by the spacing
+    /// of the keys, so unevenly spaced keys don't overshoot.
+    CatmullRom,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe<T> {
+    pub time: f32,
+    pub value: T,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Track<T> {
+    pub interpolation: Interpolation,
+    keyframes: Vec<Keyframe<T>>,
+}
+
+impl<T: Animatable> Track<T> {
+    pub fn new(interpolation: Interpolation) -> Self {
+        Track {
+            interpolation,
+            keyframes: Vec::new(),
+        }
+    }
+
+    /// Adds a keyframe, keeping the keyframes ordered by time.
+    pub fn key(mut self, time: f32, value: T) -> Self {
+        let index = self.keyframes.partition_point(|k| k.time <= time);
+        self.keyframes.insert(index, Keyframe { time, value });
+        self
+    }
+
+    /// Evaluates the track at `time`, holding the first and last values outside of the keyed
+    /// range. Returns `None` for a track without keyframes.
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let keys = &self.keyframes;
+        let first = keys.first()?;
+        let last = keys.last()?;
+
+        if time <= first.time {
+            return Some(first.value);
+        }
+        if time >= last.time {
+            return Some(last.value);
+        }
+
+        let i = keys.partition_point(|k| k.time <= time) - 1;
+        let (k1, k2) = (keys[i], keys[i + 1]);
+        let t = (time - k1.time) / (k2.time - k1.time);
+
+        let value = match self.interpolation {
+            Interpolation::Linear => k1.value * (1. - t) + k2.value * t,
+            Interpolation::CatmullRom => {
+                // the end keys stand in for their missing neighbors, one segment further out
+                let span = k2.time - k1.time;
+                let k0 = match i.checked_sub(1) {
+                    Some(j) => keys[j],
+                    None => Keyframe {
+                        time: k1.time - span,
+                        value: k1.value,
+                    },
+                };
+                let k3 = keys.get(i + 2).copied().unwrap_or(Keyframe {
+                    time: k2.time + span,
+                    value: k2.value,
+                });
+
+                // Hermite basis, with the tangents at k1 and k2 taken over the neighboring keys
+                // and scaled from their time span to the segment's
+                let s1 = span / (k2.time - k0.time);
+                let s2 = span / (k3.time - k1.time);
+                let t2 = t * t;
+                let t3 = t2 * t;
+                let (h00, h10) = (2. * t3 - 3. * t2 + 1., t3 - 2. * t2 + t);
+                let (h01, h11) = (3. * t2 - 2. * t3, t3 - t2);
+
+                k0.value * (-s1 * h10)
+                    + k1.value * (h00 - s2 * h11)
+                    + k2.value * (h01 + s1 * h10)
+                    + k3.value * (s2 * h11)
+            }
+        };
+
+        Some(value)
+    }
+}
+
+/// Translation, rotation (Euler angles in degrees, applied Y-X-Z) and scale tracks composing an
+/// object transform. Empty tracks fall back to the identity.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransformTrack {
+    pub translation: Track<Vec3>,
+    pub rotation: Track<Vec3>,
+    pub scale: Track<Vec3>,
+}
+
+impl TransformTrack {
+    pub fn sample(&self, time: f32) -> Affine3A {
+        let translation = self.translation.sample(time).unwrap_or(Vec3::ZERO);
+        let rotation = self.rotation.sample(time).unwrap_or(Vec3::ZERO);
+        let scale = self.scale.sample(time).unwrap_or(Vec3::ONE);
+
+        Affine3A::from_scale_rotation_translation(
+            scale,
+            Quat::from_euler(
+                EulerRot::YXZ,
+                rotation.y.to_radians(),
+                rotation.x.to_radians(),
+                rotation.z.to_radians(),
+            ),
+            translation,
+        )
+    }
+}
+
+/// Animated color of a material. Emission counts as ambient color, base and albedo colors as
+/// diffuse and the colors of mirrors and metals as specular; other materials ignore the color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorParameter {
+    Ambient,
+    Diffuse,
+    Specular,
+}
+
+/// Animated scalar of a Phong material.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalarParameter {
+    SpecularPower,
+    Reflection,
+    Transparency,
+    Roughness,
+}
+
+/// A single animated property of the scene. Lights are addressed by their index in the
+/// [`ObjectStore`], objects and materials by their handles.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Channel {
+    CameraEye(Track<Vec3>),
+    CameraLook(Track<Vec3>),
+    LightPosition(usize, Track<Vec3>),
+    ObjectTransform(ObjectId, TransformTrack),
+    MaterialColor(MaterialId, ColorParameter, Track<Color>),
+    MaterialScalar(MaterialId, ScalarParameter, Track<f32>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animation {
+    pub channels: Vec<Channel>,
+    pub frame_rate: f32,
+    /// Fraction of a frame's duration during which the shutter is open.
+    pub shutter: f32,
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Animation {
+            channels: Vec::new(),
+            frame_rate: 24.,
+            shutter: 0.5,
+        }
+    }
+}
+
+impl Animation {
+    pub fn frame_time(&self, frame: u32) -> f32 {
+        frame as f32 / self.frame_rate
+    }
+
+    /// Poses the scene for `frame`. Animated transforms and the camera are evaluated at both ends
+    /// of the frame's shutter interval, so multi-sampled renders pick up motion blur.
+    pub fn apply(&self, store: &mut ObjectStore, frame: u32) {
+        let open = self.frame_time(frame);
+        let close = open + self.shutter / self.frame_rate;
+
+        store.camera.shutter_open = open;
+        store.camera.shutter_close = close;
+
+        let mut eye = (store.camera.eye_pointer, store.camera.eye_pointer);
+        let mut look = (store.camera.look_pointer, store.camera.look_pointer);
+
+        for channel in &self.channels {
+            match channel {
+                Channel::CameraEye(track) => {
+                    eye = sample_interval(track, open, close).unwrap_or(eye);
+                }
+                Channel::CameraLook(track) => {
+                    look = sample_interval(track, open, close).unwrap_or(look);
+                }
+                Channel::LightPosition(light, track) => {
+                    if let (Some(light), Some(position)) =
+                        (store.lights_mut().nth(*light), track.sample(open))
+                    {
+                        light.position = position;
+                    }
+                }
+                Channel::ObjectTransform(object, track) => {
+                    let Some(object) = store.object_mut(*object) else {
+                        continue;
+                    };
+
+                    let (start, end) = (track.sample(open), track.sample(close));
+                    match object {
+                        Object::Moving(moving) => moving.set_motion(start, end, open, close),
+                        _ => *object = Moving::new(object.clone(), start, end, open, close).into(),
+                    }
+                }
+                Channel::MaterialColor(material, parameter, track) => {
+                    let (Some(material), Some(color)) =
+                        (store.material_mut(*material), track.sample(open))
+                    else {
+                        continue;
+                    };
+
+                    match (material, parameter) {
+                        (Material::Phong(surface), ColorParameter::Ambient) => {
+                            surface.ambient = color
+                        }
+                        (Material::Phong(surface), ColorParameter::Diffuse) => {
+                            surface.diffuse = color
+                        }
+                        (Material::Phong(surface), ColorParameter::Specular) => {
+                            surface.specular = color
+                        }
+                        (Material::Pbr(pbr), ColorParameter::Ambient) => pbr.emission = color,
+                        (Material::Pbr(pbr), ColorParameter::Diffuse) => pbr.base_color = color,
+                        (Material::Lambertian(lambertian), ColorParameter::Diffuse) => {
+                            lambertian.albedo = color
+                        }
+                        (Material::Plastic(plastic), ColorParameter::Diffuse) => {
+                            plastic.diffuse = color
+                        }
+                        (Material::Mirror(mirror), ColorParameter::Specular) => {
+                            mirror.color = color
+                        }
+                        (Material::RoughConductor(conductor), ColorParameter::Specular) => {
+                            conductor.color = color
+                        }
+                        (Material::Emissive(emissive), ColorParameter::Ambient) => {
+                            emissive.color = color
+                        }
+                        _ => {}
+                    }
+                }
+                Channel::MaterialScalar(material, parameter, track) => {
+                    if let (Some(Material::Phong(surface)), Some(value)) =
+                        (store.material_mut(*material), track.sample(open))
+                    {
+                        match parameter {
+                            ScalarParameter::SpecularPower => surface.specular_power = value,
+                            ScalarParameter::Reflection => surface.reflection = value,
+                            ScalarParameter::Transparency => surface.transparency = value,
+                            ScalarParameter::Roughness => surface.roughness = value,
+                        }
+                    }
+                }
+            }
+        }
+
+        store.camera.eye_pointer = eye.0;
+        store.camera.look_pointer = look.0;
+        store.camera.motion = (eye.0 != eye.1 || look.0 != look.1).then_some(CameraMotion {
+            eye_pointer: eye.1,
+            look_pointer: look.1,
+        });
+        store.camera.calculate_first_ray();
+    }
+}
+
+fn sample_interval<T: Animatable>(track: &Track<T>, open: f32, close: f32) -> Option<(T, T)> {
+    Some((track.sample(open)?, track.sample(close)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(interpolation: Interpolation) -> Track<f32> {
+        Track::new(interpolation)
+            .key(2., 4.)
+            .key(0., 0.)
+            .key(1., 2.)
+            .key(3., 3.)
+    }
+
+    #[test]
+    fn linear_interpolates_between_keys() {
+        let track = track(Interpolation::Linear);
+        assert_eq!(track.sample(0.5), Some(1.));
+        assert_eq!(track.sample(1.5), Some(3.));
+        assert_eq!(track.sample(2.75), Some(3.25));
+        assert_eq!(Track::<f32>::new(Interpolation::Linear).sample(1.), None);
+    }
+
+    #[test]
+    fn holds_values_outside_the_keys() {
+        for interpolation in [Interpolation::Linear, Interpolation::CatmullRom] {
+            let track = track(interpolation);
+            assert_eq!(track.sample(-1.), Some(0.));
+            assert_eq!(track.sample(0.), Some(0.));
+            assert_eq!(track.sample(3.), Some(3.));
+            assert_eq!(track.sample(10.), Some(3.));
+        }
+
+        let single = Track::new(Interpolation::CatmullRom).key(1., Vec3::ONE);
+        assert_eq!(single.sample(0.), Some(Vec3::ONE));
+        assert_eq!(single.sample(2.), Some(Vec3::ONE));
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_keys() {
+        let track = track(Interpolation::CatmullRom);
+        for (time, value) in [(0., 0.), (1., 2.), (2., 4.), (3., 3.)] {
+            let sampled = track.sample(time).unwrap();
+            assert!((sampled - value).abs() < 1e-6, "{sampled} at {time}");
+        }
+
+        // the spline keeps the slope through the key at 2 rather than turning sharply like the
+        // linear interpolation does
+        let curve = track.sample(2.2).unwrap();
+        let linear = self::track(Interpolation::Linear).sample(2.2).unwrap();
+        assert!(curve > linear + 0.1, "{curve} vs {linear}");
+    }
+
+    #[test]
+    fn catmull_rom_follows_unevenly_spaced_keys() {
+        // keys along a straight line, with a long gap in between
+        let track = Track::new(Interpolation::CatmullRom)
+            .key(0., 0.)
+            .key(1., 2.)
+            .key(5., 10.)
+            .key(6., 12.);
+
+        for time in [1.5, 2., 3., 4.5] {
+            let sampled = track.sample(time).unwrap();
+            assert!((sampled - 2. * time).abs() < 1e-5, "{sampled} at {time}");
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use std::{env, fs, ops::ControlFlow, path::Path};
+
+use eyre::{bail, Result, WrapErr};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use indicatif_log_bridge::LogWrapper;
+use rust_tracer::{import::obj::ObjOptions, output, raytracer::Raytracer};
+
+fn main() -> Result<()> {
+    let progress = init_logging()?;
+    let mut args = env::args().skip(1).collect::<Vec<_>>();
+    let options = obj_options(&mut args)?;
+
+    match args.first().map(String::as_str) {
+        Some("render") => render(&progress, &options, &args[1..]),
+        Some("sequence") => render_sequence(&progress, &options, &args[1..]),
+        Some(command) => bail!("unknown command `{command}`"),
+        None => bail!(
+            "usage: rust_tracer_cli [--subdivide <loop|catmull-clark>:<levels>] <render|sequence> ..."
+        ),
+    }
+}
+
+/// Removes the `--subdivide <scheme>:<levels>` option from `args`, returning the OBJ options it
+/// sets.
+fn obj_options(args: &mut Vec<String>) -> Result<ObjOptions> {
+    let mut options = ObjOptions::default();
+    if let Some(i) = args.iter().position(|arg| arg == "--subdivide") {
+        let Some(subdivision) = args.get(i + 1) else {
+            bail!("--subdivide needs a scheme and levels, such as catmull-clark:2");
+        };
+        options.subdivision = Some(subdivision.parse()?);
+        args.drain(i..=i + 1);
+    }
+
+    Ok(options)
+}
+
+/// Logs at the level set by `RUST_LOG`, `info` by default, without tearing the progress bars
+/// drawn through the returned [`MultiProgress`].
+fn init_logging() -> Result<MultiProgress> {
+    let logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+    let level = logger.filter();
+
+    let progress = MultiProgress::new();
+    LogWrapper::new(progress.clone(), logger).try_init()?;
+    log::set_max_level(level);
+
+    Ok(progress)
+}
+
+/// Adds a progress bar counting `len` rows of pixels, with the time left.
+fn progress_bar(progress: &MultiProgress, len: u64) -> Result<ProgressBar> {
+    let bar = progress.add(ProgressBar::new(len));
+    bar.set_style(ProgressStyle::with_template(
+        "{msg} [{wide_bar}] {percent:>3}% {elapsed_precise}, {eta} left",
+    )?);
+    Ok(bar)
+}
+
+fn parse_size(size: &[String]) -> Result<(u32, u32)> {
+    let width: u32 = match size.first() {
+        Some(width) => width.parse().wrap_err("parsing width")?,
+        None => 800,
+    };
+    let height: u32 = match size.get(1) {
+        Some(height) => height.parse().wrap_err("parsing height")?,
+        None => width,
+    };
+
+    Ok((width, height))
+}
+
+/// `render <scene.obj> <output> [width] [height]` renders the scene as a single PNG image.
+fn render(progress: &MultiProgress, options: &ObjOptions, args: &[String]) -> Result<()> {
+    let [scene, output, size @ ..] = args else {
+        bail!("usage: render <scene.obj> <output> [width] [height]");
+    };
+
+    let (width, height) = parse_size(size)?;
+    let mut raytracer = Raytracer::new_with(scene, options, width, height)?;
+
+    let bar = progress_bar(progress, height as u64)?;
+    let image = raytracer
+        .render_with_progress(|update| {
+            bar.set_position(update.rows_done as u64);
+            ControlFlow::Continue(())
+        })
+        .expect("the render is never cancelled");
+    bar.finish_and_clear();
+
+    let path = Path::new(output);
+    output::write_png(path, width, height, &image)?;
+    log::info!("wrote {}", path.display());
+
+    Ok(())
+}
+
+/// `sequence <scene.obj> <output-dir> <first-frame> <last-frame> [width] [height]` renders the
+/// scene's animation as numbered PNG images.
+fn render_sequence(progress: &MultiProgress, options: &ObjOptions, args: &[String]) -> Result<()> {
+    let [scene, output, first, last, size @ ..] = args else {
+        bail!(
+            "usage: sequence <scene.obj> <output-dir> <first-frame> <last-frame> [width] [height]"
+        );
+    };
+
+    let first: u32 = first.parse().wrap_err("parsing first frame")?;
+    let last: u32 = last.parse().wrap_err("parsing last frame")?;
+    if first > last {
+        bail!("first frame {first} comes after last frame {last}");
+    }
+    let (width, height) = parse_size(size)?;
+
+    let output = Path::new(output);
+    fs::create_dir_all(output).wrap_err_with(|| format!("creating {}", output.display()))?;
+
+    let mut raytracer = Raytracer::new_with(scene, options, width, height)?;
+
+    let frames = last - first + 1;
+    let bar = progress_bar(progress, frames as u64 * height as u64)?;
+
+    for frame in first..=last {
+        bar.set_message(format!("frame {frame}"));
+        let done = (frame - first) as u64 * height as u64;
+        let image = raytracer
+            .render_frame_with_progress(frame, |update| {
+                bar.set_position(done + update.rows_done as u64);
+                ControlFlow::Continue(())
+            })
+            .expect("the render is never cancelled");
+
+        let path = output.join(format!("frame_{frame:04}.png"));
+        output::write_png(&path, width, height, &image)?;
+        log::info!("wrote {}", path.display());
+    }
+    bar.finish_and_clear();
+
+    Ok(())
+}
@@ -0,0 +1,248 @@
+use crate::{aabb::Aabb, ray::Ray, stats::count_node_tests};
+
+const MAX_LEAF_SIZE: usize = 4;
+
+/// Bounding volume hierarchy over a list of primitives. Building the hierarchy reorders the
+/// primitives so that every leaf references a contiguous range of them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+}
+
+/// Leaves reference `count` primitives starting at `offset`. Interior nodes have a `count` of
+/// zero, their left child directly follows them and `offset` is the index of the right child.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Node {
+    bounds: Aabb,
+    offset: u32,
+    count: u32,
+}
+
+impl Bvh {
+    pub fn build<T>(items: &mut [T], bounds: impl Fn(&T) -> Aabb) -> Self {
+        let mut entries = items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (i, bounds(item)))
+            .collect::<Vec<_>>();
+
+        let mut bvh = Bvh { nodes: Vec::new() };
+        if !entries.is_empty() {
+            bvh.build_node(&mut entries, 0);
+        }
+
+        let order = entries.iter().map(|(i, _)| *i).collect::<Vec<_>>();
+        apply_order(items, order);
+
+        bvh
+    }
+
+    fn build_node(&mut self, entries: &mut [(usize, Aabb)], offset: usize) -> usize {
+        let bounds = entries
+            .iter()
+            .fold(Aabb::EMPTY, |bounds, (_, b)| bounds.union(*b));
+
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            bounds,
+            offset: offset as u32,
+            count: entries.len() as u32,
+        });
+
+        if entries.len() <= MAX_LEAF_SIZE {
+            return index;
+        }
+
+        let centroids = Aabb::from_points(entries.iter().map(|(_, b)| b.centroid()));
+        let extent = centroids.max - centroids.min;
+        let axis = if extent.x > extent.y && extent.x > extent.z {
+            0
+        } else if extent.y > extent.z {
+            1
+        } else {
+            2
+        };
+
+        let mid = entries.len() / 2;
+        entries.select_nth_unstable_by(mid, |(_, a), (_, b)| {
+            a.centroid()[axis].total_cmp(&b.centroid()[axis])
+        });
+
+        let (left, right) = entries.split_at_mut(mid);
+        self.build_node(left, offset);
+        let right = self.build_node(right, offset + mid);
+
+        self.nodes[index].offset = right as u32;
+        self.nodes[index].count = 0;
+
+        index
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        self.nodes.first().map_or(Aabb::EMPTY, |node| node.bounds)
+    }
+
+    /// Finds the closest hit along `ray`, calling `intersect` with the index of every primitive
+    /// whose leaf bounds are pierced closer than the best hit found so far. Counts the node tests
+    /// in the [render stats](crate::stats::RenderStats).
+    pub fn closest_hit<H>(
+        &self,
+        ray: &Ray,
+        mut intersect: impl FnMut(usize) -> Option<(f32, H)>,
+    ) -> Option<(f32, H)> {
+        let inv_direction = ray.direction.recip();
+
+        let mut closest: Option<(f32, H)> = None;
+        let mut stack = Vec::with_capacity(32);
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        let mut tests = 0;
+        while let Some(index) = stack.pop() {
+            tests += 1;
+            let node = self.nodes[index];
+            let max_distance = closest.as_ref().map_or(f32::INFINITY, |(s, _)| *s);
+            if node
+                .bounds
+                .intersect(ray, inv_direction, max_distance)
+                .is_none()
+            {
+                continue;
+            }
+
+            if node.count == 0 {
+                stack.push(node.offset as usize);
+                stack.push(index + 1);
+                continue;
+            }
+
+            let start = node.offset as usize;
+            for i in start..start + node.count as usize {
+                if let Some((s, hit)) = intersect(i) {
+                    if s > 0. && closest.as_ref().is_none_or(|(best, _)| s < *best) {
+                        closest = Some((s, hit));
+                    }
+                }
+            }
+        }
+        count_node_tests(tests);
+
+        closest
+    }
+
+    /// Calls `visit` with the index of every primitive whose leaf bounds `ray` pierces, counting
+    /// the node tests like [`Bvh::closest_hit`].
+    pub fn for_each_candidate(&self, ray: &Ray, mut visit: impl FnMut(usize)) {
+        let inv_direction = ray.direction.recip();
+
+        let mut stack = Vec::with_capacity(32);
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        let mut tests = 0;
+        while let Some(index) = stack.pop() {
+            tests += 1;
+            let node = self.nodes[index];
+            if node
+                .bounds
+                .intersect(ray, inv_direction, f32::INFINITY)
+                .is_none()
+            {
+                continue;
+            }
+
+            if node.count == 0 {
+                stack.push(node.offset as usize);
+                stack.push(index + 1);
+            } else {
+                let start = node.offset as usize;
+                (start..start + node.count as usize).for_each(&mut visit);
+            }
+        }
+        count_node_tests(tests);
+    }
+}
+
+fn apply_order<T>(items: &mut [T], order: Vec<usize>) {
+    // `order[i]` is the index of the item that belongs at position `i`; follow each permutation
+    // cycle, swapping items into place.
+    let mut order = order;
+    for i in 0..order.len() {
+        let mut current = i;
+        while order[current] != i {
+            let next = order[current];
+            items.swap(current, next);
+            order[current] = current;
+            current = next;
+        }
+        order[current] = current;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        object::{sphere::Sphere, Intersectable},
+        object_store::MaterialId,
+    };
+
+    #[test]
+    fn closest_hit_matches_brute_force() {
+        let mut rng = SmallRng::seed_from_u64(3);
+        let mut point = |extent: f32| {
+            Vec3::new(rng.gen(), rng.gen(), rng.gen()) * 2. * extent - Vec3::splat(extent)
+        };
+
+        let spheres = (0..200)
+            .map(|_| Sphere::new(point(10.), 0.5, MaterialId(0)))
+            .collect::<Vec<_>>();
+        let mut ordered = spheres.clone();
+        let bvh = Bvh::build(&mut ordered, Intersectable::bounds);
+
+        // building only reorders the primitives
+        assert_eq!(ordered.len(), spheres.len());
+        assert!(spheres.iter().all(|sphere| ordered.contains(sphere)));
+
+        let mut hits = 0;
+        for _ in 0..500 {
+            let origin = point(15.);
+            let ray = Ray::new(origin, (point(10.) - origin).normalize(), 0.);
+
+            let expected = spheres
+                .iter()
+                .filter_map(|sphere| sphere.intersect(&ray))
+                .map(|hit| hit.distance)
+                .filter(|&distance| distance > 0.)
+                .min_by(f32::total_cmp);
+            let found = bvh.closest_hit(&ray, |i| {
+                let hit = ordered[i].intersect(&ray)?;
+                Some((hit.distance, ()))
+            });
+
+            assert_eq!(found.map(|(distance, _)| distance), expected);
+            hits += expected.is_some() as usize;
+        }
+        assert!(hits > 50, "only {hits} rays hit anything");
+    }
+
+    #[test]
+    fn applies_permutations() {
+        let mut items = ['a', 'b', 'c', 'd', 'e'];
+        apply_order(&mut items, vec![3, 0, 4, 1, 2]);
+        assert_eq!(items, ['d', 'a', 'e', 'b', 'c']);
+
+        let mut empty: [char; 0] = [];
+        apply_order(&mut empty, Vec::new());
+        let bvh = Bvh::build(&mut empty, |_| Aabb::EMPTY);
+        assert_eq!(
+            bvh.closest_hit(&Ray::new(Vec3::ZERO, Vec3::X, 0.), |_| Some((1., ()))),
+            None
+        );
+    }
+}
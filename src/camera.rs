@@ -1,12 +1,24 @@
-use std::time::Instant;
+use std::{
+    f32::consts::PI,
+    ops::ControlFlow,
+    time::{Duration, Instant},
+};
 
-use glam::Vec3;
+use glam::{Vec2, Vec3};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
 
 use crate::{
     color::Color,
     consts::FAR_AWAY,
-    object::{Intersectable, Object},
-    object_store::OBJECT_STORE,
+    inspect::{LightContribution, RayKind, RayTrace, TraceHit},
+    light::Light,
+    material::{cosine_direction, facing, to_world, Bsdf, Material, Scatter},
+    medium,
+    object::Hit,
+    object_store::{MaterialId, ObjectId, ObjectStore},
+    ray::Ray,
+    stats::{take_traversal_tests, RayCounters, RenderStats},
+    surface::SurfaceMaterial,
 };
 
 #[derive(Default)]
@@ -24,17 +36,32 @@ pub struct Camera {
     pub width: u32,
     pub height: u32,
 
-    first_ray: Vec3,
-    gaze: Vec3,
+    pub projection: Projection,
 
-    scrnx: Vec3,
-    scrny: Vec3,
+    pub shutter_open: f32,
+    pub shutter_close: f32,
+    pub motion: Option<CameraMotion>,
+
+    pub samples: u32,
+    pub glossy_samples: u32,
+    pub volume_steps: u32,
+
+    view: View,
 
     pub max_level: u32,
+    pub ray_budget: Option<u32>,
 
     pub background: Color,
 
+    pub ambient_occlusion: Option<AmbientOcclusion>,
+    pub render_mode: RenderMode,
+
     frame_buffer: Vec<Color>,
+    /// Object and material hit by the primary ray through each pixel, `None` for the background.
+    id_buffer: Vec<Option<(ObjectId, MaterialId)>>,
+
+    counters: RayCounters,
+    stats: RenderStats,
 }
 
 impl Camera {
@@ -51,8 +78,18 @@ impl Camera {
             hfov,
             width: desc.width,
             height: desc.height,
+            projection: desc.projection,
+            shutter_open: desc.shutter_open,
+            shutter_close: desc.shutter_close,
+            motion: desc.motion,
+            samples: desc.samples,
+            glossy_samples: desc.glossy_samples,
+            volume_steps: desc.volume_steps,
             max_level: desc.max_level,
+            ray_budget: desc.ray_budget,
             background: desc.background,
+            ambient_occlusion: desc.ambient_occlusion,
+            render_mode: desc.render_mode,
             ..Default::default()
         }
     }
@@ -66,142 +103,694 @@ impl Camera {
     }
 
     pub fn calculate_first_ray(&mut self) -> Vec3 {
-        let gaze = self.look_pointer - self.eye_pointer;
-        self.gaze = gaze.normalize();
+        self.view = self.view_from(self.eye_pointer, self.look_pointer);
+        self.view.first_ray
+    }
 
-        self.scrnx = self.gaze.cross(self.up_pointer).normalize();
-        self.scrny = self.scrnx.cross(self.gaze).normalize();
+    fn view_from(&self, eye_pointer: Vec3, look_pointer: Vec3) -> View {
+        let gaze = look_pointer - eye_pointer;
+
+        let right = gaze.cross(self.up_pointer).normalize();
+        let up = right.cross(gaze).normalize();
 
         let dist = gaze.length() * 2.;
         let magnitude = dist * self.hfov.to_radians().tan() / self.width as f32;
-        self.scrnx = self.scrnx * magnitude;
+        let scrnx = right * magnitude;
 
         let magnitude = dist * self.vfov.to_radians().tan() / self.height as f32;
-        self.scrny = self.scrny * magnitude;
+        let scrny = up * magnitude;
 
-        let offset = -self.scrnx * self.width as f32 / 2. + self.scrny * self.height as f32 / 2.;
-        self.first_ray = self.look_pointer - self.eye_pointer + offset;
+        let offset = -scrnx * self.width as f32 / 2. + scrny * self.height as f32 / 2.;
 
-        self.first_ray
+        View {
+            eye: eye_pointer,
+            first_ray: gaze + offset,
+            gaze: gaze.normalize(),
+            right,
+            up,
+            scrnx,
+            scrny,
+        }
     }
 
-    pub fn trace(&mut self) {
+    /// Returns the view at `time`, interpolating towards the end pose of the camera's motion over
+    /// the shutter interval.
+    fn view_at(&self, time: f32) -> View {
+        let Some(motion) = self.motion else {
+            return self.view;
+        };
+
+        let duration = self.shutter_close - self.shutter_open;
+        if duration <= 0. {
+            return self.view;
+        }
+
+        let t = ((time - self.shutter_open) / duration).clamp(0., 1.);
+        self.view_from(
+            self.eye_pointer.lerp(motion.eye_pointer, t),
+            self.look_pointer.lerp(motion.look_pointer, t),
+        )
+    }
+
+    /// Renders the objects, lights and volumes of `scene` into the frame buffer. The camera of
+    /// `scene` itself is ignored.
+    pub fn trace(&mut self, scene: &ObjectStore) {
+        let _ = self.trace_with_progress(scene, |_| ControlFlow::Continue(()));
+    }
+
+    /// Renders like [`Camera::trace`], reporting the progress to `progress` after every row of
+    /// pixels. Returning [`ControlFlow::Break`] from `progress` cancels the render, leaving the
+    /// remaining rows of the frame buffer black, and is passed on to the caller.
+    pub fn trace_with_progress(
+        &mut self,
+        scene: &ObjectStore,
+        mut progress: impl FnMut(RenderProgress) -> ControlFlow<()>,
+    ) -> ControlFlow<()> {
         let start = Instant::now();
         self.frame_buffer = vec![Color::default(); self.width as usize * self.height as usize];
+        self.id_buffer = vec![None; self.width as usize * self.height as usize];
         //self.depth_buffer = vec![f32::MAX; self.width as usize * self.height as usize];
 
-        println!("width = {}, height = {}", self.width, self.height);
+        self.counters = RayCounters::default();
+        scene.take_intersection_tests();
+        take_traversal_tests();
+        let setup = start.elapsed();
+
+        let start = Instant::now();
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut flow = ControlFlow::Continue(());
 
         for y in 0..self.height {
             for x in 0..self.width {
-                let ray =
-                    (self.first_ray + x as f32 * self.scrnx - y as f32 * self.scrny).normalize();
-
-                let color = self.intersect_and_shade(None, self.eye_pointer, ray, 0);
+                let (color, ids) = if self.samples <= 1 {
+                    self.sample(scene, x as f32 + 0.5, y as f32 + 0.5, self.shutter_open)
+                } else {
+                    let mut color = Color::default();
+                    let mut ids = None;
+                    for i in 0..self.samples {
+                        let time = self.shutter_open
+                            + (self.shutter_close - self.shutter_open) * rng.gen::<f32>();
+                        let (sample, sample_ids) = self.sample(
+                            scene,
+                            x as f32 + rng.gen::<f32>(),
+                            y as f32 + rng.gen::<f32>(),
+                            time,
+                        );
+                        color += sample;
+                        if i == 0 {
+                            ids = sample_ids;
+                        }
+                    }
+                    (color * (1. / self.samples as f32), ids)
+                };
 
                 self.frame_buffer[(y * self.width + x) as usize] = color;
+                self.id_buffer[(y * self.width + x) as usize] = ids;
+            }
+
+            flow = progress(RenderProgress {
+                rows_done: y + 1,
+                rows: self.height,
+                elapsed: start.elapsed(),
+            });
+            if flow.is_break() {
+                log::info!("render cancelled after {} of {} rows", y + 1, self.height);
+                break;
             }
         }
-        println!("Rendering took {:?}", start.elapsed());
+
+        let (node_tests, triangle_tests) = take_traversal_tests();
+        let mut stats = RenderStats {
+            width: self.width,
+            height: self.height,
+            intersection_tests: scene.take_intersection_tests(),
+            node_tests,
+            triangle_tests,
+            phases: vec![("setup", setup), ("tracing", start.elapsed())],
+            ..Default::default()
+        };
+        self.counters.report(&mut stats);
+        log::info!("{stats}");
+        self.stats = stats;
+
+        flow
     }
 
-    fn intersect_and_shade(
+    /// Returns the ray counts and timings of the last frame rendered by [`Camera::trace`].
+    pub fn stats(&self) -> &RenderStats {
+        &self.stats
+    }
+
+    /// Traces the primary ray through `(x, y)` and returns its color along with the object and
+    /// material it hit.
+    fn sample(
         &self,
-        source: Option<&Object>,
-        pos: Vec3,
-        ray: Vec3,
-        level: u32,
-    ) -> Color {
-        let intersection = self.intersect(source, pos, ray);
+        scene: &ObjectStore,
+        x: f32,
+        y: f32,
+        time: f32,
+    ) -> (Color, Option<(ObjectId, MaterialId)>) {
+        let Some(ray) = self.primary_ray(x, y, time) else {
+            return (self.background, None);
+        };
+        self.counters.primary(self.ray_budget);
+
+        let hit = scene.intersect(&ray);
+        let color = self.shade_hit(scene, &ray, hit.as_ref().map(|(_, hit)| hit), 0, None);
+        (color, hit.map(|(object, hit)| (object, hit.material)))
+    }
+
+    /// Generates the primary ray at `time` through the continuous pixel coordinate `(x, y)`,
+    /// where `(0, 0)` is the top left corner of the image. Returns `None` if the coordinate lies
+    /// outside of the projection's image area.
+    pub fn primary_ray(&self, x: f32, y: f32, time: f32) -> Option<Ray> {
+        let width = self.width as f32;
+        let height = self.height as f32;
+
+        let view = self.view_at(time);
 
-        let color = match intersection {
-            Some((distance, hit, ray, normal, object_hit)) if distance > 0. => {
-                self.shade(hit, ray, normal, object_hit, level)
+        match self.projection {
+            Projection::Perspective => {
+                let ray = (view.first_ray + x * view.scrnx - y * view.scrny).normalize();
+                Some(Ray::new(view.eye, ray, time))
             }
-            _ => self.background,
-        };
+            Projection::Orthographic {
+                height: view_height,
+            } => {
+                let view_width = view_height * width / height;
+                let offset = view.right * (x / width - 0.5) * view_width
+                    + view.up * (0.5 - y / height) * view_height;
+                Some(Ray::new(view.eye + offset, view.gaze, time))
+            }
+            Projection::Fisheye { fov } => {
+                let radius = width.min(height) / 2.;
+                let px = (x - width / 2.) / radius;
+                let py = (height / 2. - y) / radius;
 
-        color
+                let r = (px * px + py * py).sqrt();
+                if r > 1. {
+                    return None;
+                }
+
+                let theta = r * fov.to_radians() / 2.;
+                let phi = py.atan2(px);
+                let ray = view.gaze * theta.cos()
+                    + (view.right * phi.cos() + view.up * phi.sin()) * theta.sin();
+                Some(Ray::new(view.eye, ray.normalize(), time))
+            }
+            Projection::Equirectangular => {
+                let longitude = (x / width - 0.5) * 2. * PI;
+                let latitude = (0.5 - y / height) * PI;
+
+                let ray = (view.gaze * longitude.cos() + view.right * longitude.sin())
+                    * latitude.cos()
+                    + view.up * latitude.sin();
+                Some(Ray::new(view.eye, ray.normalize(), time))
+            }
+        }
+    }
+
+    /// Returns the radiance arriving along `ray`, as seen by a primary ray whose closest hit is
+    /// `hit`.
+    pub fn radiance(&self, scene: &ObjectStore, ray: &Ray, hit: Option<&Hit>) -> Color {
+        self.counters.primary(self.ray_budget);
+        self.shade_hit(scene, ray, hit, 0, None)
+    }
+
+    /// Traces the primary ray through the center of pixel `(x, y)` at the start of the shutter
+    /// interval, recording every step of its shading. Returns `None` for pixels outside the frame
+    /// or the projection's image area.
+    pub fn inspect(&self, scene: &ObjectStore, x: u32, y: u32) -> Option<RayTrace> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let ray = self.primary_ray(x as f32 + 0.5, y as f32 + 0.5, self.shutter_open)?;
+
+        let mut trace = RayTrace::new(RayKind::Primary, ray);
+        self.counters.primary(self.ray_budget);
+        self.trace_ray(scene, &ray, 0, Some(&mut trace));
+        Some(trace)
     }
 
-    fn intersect(
+    /// Shades `ray`, filling in `record` with the hit, the light contributions and the secondary
+    /// rays if given.
+    fn trace_ray(
         &self,
-        source: Option<&Object>,
-        pos: Vec3,
-        ray: Vec3,
-    ) -> Option<(f32, Vec3, Vec3, Vec3, &Object)> {
-        let mut ss = FAR_AWAY;
+        scene: &ObjectStore,
+        ray: &Ray,
+        level: u32,
+        mut record: Option<&mut RayTrace>,
+    ) -> Color {
+        let hit = scene.intersect(ray);
+        if let (Some(record), Some((object, hit))) = (record.as_deref_mut(), hit) {
+            record.hit = Some(TraceHit {
+                object,
+                material: hit.material,
+                material_name: scene.material_name(hit.material).to_owned(),
+                position: ray.at(hit.distance),
+                normal: hit.normal,
+                distance: hit.distance,
+            });
+        }
 
-        let mut object_hit = None;
+        let color = self.shade_hit(
+            scene,
+            ray,
+            hit.as_ref().map(|(_, hit)| hit),
+            level,
+            record.as_deref_mut(),
+        );
+        if let Some(record) = record {
+            record.color = color;
+        }
 
-        for object in unsafe { OBJECT_STORE.objects() } {
-            if Some(object) != source {
-                let Some(s) = object.intersect(pos, ray) else {
-                    continue;
-                };
+        color
+    }
 
-                if s > 0. && s <= ss {
-                    ss = s;
-                    object_hit = Some(object);
-                }
+    /// Shades the closest hit of `ray`, or the background if it has none, and passes the color
+    /// through the volumes on the way back to the ray origin. In the ambient occlusion render
+    /// mode, returns the ambient visibility of the hit instead, or white for the background.
+    fn shade_hit(
+        &self,
+        scene: &ObjectStore,
+        ray: &Ray,
+        hit: Option<&Hit>,
+        level: u32,
+        record: Option<&mut RayTrace>,
+    ) -> Color {
+        if self.render_mode == RenderMode::AmbientOcclusion {
+            let settings = self.ambient_occlusion.unwrap_or_default();
+            let visibility = hit.map_or(1., |hit| {
+                self.ambient_visibility(scene, ray, hit, &settings)
+            });
+            return Color::new(1., 1., 1.) * visibility;
+        }
+
+        match hit {
+            Some(hit) => {
+                let color = self.shade(scene, ray, hit, level, record);
+                self.through_volumes(scene, ray, hit.distance, color)
             }
+            None => self.through_volumes(scene, ray, FAR_AWAY, self.background),
         }
+    }
 
-        let object_hit = object_hit?;
+    /// Traces a secondary ray, adding its record to the children of `record` if given. Returns
+    /// black without tracing the ray once the ray budget is used up.
+    fn trace_child(
+        &self,
+        scene: &ObjectStore,
+        kind: RayKind,
+        ray: &Ray,
+        level: u32,
+        record: Option<&mut RayTrace>,
+    ) -> Color {
+        if !self.counters.secondary() {
+            return Color::default();
+        }
 
-        let hit = pos + ray * ss;
+        let Some(record) = record else {
+            return self.trace_ray(scene, ray, level, None);
+        };
 
-        let normal = object_hit.normal(hit);
+        let mut child = RayTrace::new(kind, *ray);
+        let color = self.trace_ray(scene, ray, level, Some(&mut child));
+        record.children.push(child);
+        color
+    }
 
-        Some((ss, hit, ray, normal, object_hit))
+    fn shade(
+        &self,
+        scene: &ObjectStore,
+        ray: &Ray,
+        hit: &Hit,
+        level: u32,
+        record: Option<&mut RayTrace>,
+    ) -> Color {
+        match scene.material(hit.material) {
+            Material::Phong(surface) => self.shade_phong(scene, ray, hit, surface, level, record),
+            material => self.shade_bsdf(scene, ray, hit, material, level, record),
+        }
     }
 
-    fn shade(&self, pos: Vec3, ray: Vec3, normal: Vec3, object: &Object, level: u32) -> Color {
-        let k = -2. * ray.dot(normal);
-        let reflected_ray = normal * k + ray;
+    fn shade_phong(
+        &self,
+        scene: &ObjectStore,
+        ray: &Ray,
+        hit: &Hit,
+        surface: &SurfaceMaterial,
+        level: u32,
+        mut record: Option<&mut RayTrace>,
+    ) -> Color {
+        let pos = ray.at(hit.distance);
+        let normal = hit.normal;
 
-        let surface = object.surface();
+        let k = -2. * ray.direction.dot(normal);
+        let reflected_ray = normal * k + ray.direction;
 
-        let mut color = surface.ambient;
+        let mut color = match &self.ambient_occlusion {
+            Some(settings) => surface.ambient * self.ambient_visibility(scene, ray, hit, settings),
+            None => surface.ambient,
+        };
 
-        for light in unsafe { OBJECT_STORE.lights_mut() } {
+        for (index, light) in scene.lights().iter().enumerate() {
             let light_ray = light.lightray(pos);
 
             let mut diffuse = normal.dot(light_ray);
 
-            if diffuse > 0. {
-                let brightness = light.brightness(object, pos, light_ray);
+            if diffuse > 0. && self.counters.shadow() {
+                let shadow_ray = Ray::spawn(pos, normal, light_ray, ray.time);
+                let brightness = scene.light_brightness(light, &shadow_ray);
+                let through = self.light_transmittance(scene, &shadow_ray, light);
                 diffuse *= brightness;
-                color += surface.diffuse * diffuse;
+                let diffuse = hit.color.unwrap_or(surface.diffuse) * diffuse * through;
+                color += diffuse;
 
                 let specular = reflected_ray.dot(light_ray);
-                if specular > 0. {
-                    let specular = specular.powf(surface.specular_power);
-                    color += surface.specular * specular;
+                let specular = if specular > 0. {
+                    surface.specular * specular.powf(surface.specular_power) * through
+                } else {
+                    Color::default()
+                };
+                color += specular;
+
+                if let Some(record) = record.as_deref_mut() {
+                    record.lights.push(LightContribution {
+                        light: index,
+                        brightness,
+                        diffuse,
+                        specular,
+                    });
                 }
             }
         }
 
         let k = surface.reflection;
         if k > 0. && level < self.max_level {
-            let reflection_color =
-                self.intersect_and_shade(Some(object), pos, reflected_ray, level + 1);
-            color += reflection_color * k;
+            if surface.roughness > 0. {
+                color +=
+                    self.trace_glossy(scene, ray, hit, level + 1, record.as_deref_mut(), |u| {
+                        Scatter {
+                            transmission: None,
+                            ..surface.scatter(normal, -ray.direction, u)
+                        }
+                    });
+            } else {
+                let reflection_color = self.trace_child(
+                    scene,
+                    RayKind::Reflection,
+                    &Ray::spawn(pos, normal, reflected_ray, ray.time),
+                    level + 1,
+                    record.as_deref_mut(),
+                );
+                color += reflection_color * k;
+            }
         }
 
         let k = surface.transparency;
-        if k > 0. {
+        if k > 0. && level < self.max_level {
             color *= 1. - k;
-            let trans_color = self.intersect_and_shade(Some(object), pos, ray, level);
-            color += trans_color * k;
+            if surface.roughness > 0. {
+                color += self.trace_glossy(scene, ray, hit, level + 1, record, |u| Scatter {
+                    reflection: None,
+                    ..surface.scatter(normal, -ray.direction, u)
+                });
+            } else {
+                let trans_color = self.trace_child(
+                    scene,
+                    RayKind::Transmission,
+                    &Ray::spawn(pos, normal, ray.direction, ray.time),
+                    level + 1,
+                    record,
+                );
+                color += trans_color * k;
+            }
+        }
+
+        color
+    }
+
+    /// Traces secondary rays at `level` from the hit of `ray`, in the directions of the
+    /// reflections and transmissions drawn by `scatter`, and returns the average of their weighted
+    /// colors. The first bounce is averaged over [`Camera::glossy_samples`] draws, deeper ones
+    /// follow a single draw to keep the number of rays from growing exponentially. Draws without
+    /// a direction count as black.
+    fn trace_glossy(
+        &self,
+        scene: &ObjectStore,
+        ray: &Ray,
+        hit: &Hit,
+        level: u32,
+        mut record: Option<&mut RayTrace>,
+        scatter: impl Fn(Vec2) -> Scatter,
+    ) -> Color {
+        let pos = ray.at(hit.distance);
+        let count = self.glossy_samples_at(level);
+        let mut rng = ray_rng(ray, Sampling::Glossy);
+
+        let mut color = Color::default();
+        for _ in 0..count {
+            let scatter = scatter(Vec2::new(rng.gen(), rng.gen()));
+            for (kind, sample) in [
+                (RayKind::Reflection, scatter.reflection),
+                (RayKind::Transmission, scatter.transmission),
+            ] {
+                let Some(sample) = sample else {
+                    continue;
+                };
+
+                let scattered = self.trace_child(
+                    scene,
+                    kind,
+                    &Ray::spawn(pos, hit.normal, sample.direction, ray.time),
+                    level,
+                    record.as_deref_mut(),
+                );
+                color += scattered * sample.weight;
+            }
+        }
+
+        color * (1. / count as f32)
+    }
+
+    /// Number of directions averaged over glossy lobes by secondary rays spawned at `level`.
+    fn glossy_samples_at(&self, level: u32) -> u32 {
+        if level <= 1 {
+            self.glossy_samples.max(1)
+        } else {
+            1
+        }
+    }
+
+    /// Returns the share of cosine-weighted rays over the hemisphere facing the viewer at the hit
+    /// of `ray` that escape without hitting anything within the ambient occlusion radius. Only
+    /// the rays within the ray budget are cast; without any, the hit counts as unoccluded.
+    fn ambient_visibility(
+        &self,
+        scene: &ObjectStore,
+        ray: &Ray,
+        hit: &Hit,
+        settings: &AmbientOcclusion,
+    ) -> f32 {
+        let pos = ray.at(hit.distance);
+        let normal = facing(hit.normal, -ray.direction);
+        let mut rng = ray_rng(ray, Sampling::AmbientOcclusion);
+
+        let mut cast = 0;
+        let mut escaped = 0;
+        while cast < settings.samples && self.counters.shadow() {
+            let direction = to_world(normal, cosine_direction(rng.gen(), rng.gen()));
+            let probe = Ray::spawn(pos, normal, direction, ray.time);
+            if !scene.occluded(&probe, settings.radius) {
+                escaped += 1;
+            }
+            cast += 1;
+        }
+
+        if cast == 0 {
+            return 1.;
+        }
+        escaped as f32 / cast as f32
+    }
+
+    /// Shades a hit through the material's [`Bsdf`]. Light brightness is scaled so that a white
+    /// Lambertian surface facing a light reflects its full brightness, as under Phong shading.
+    /// The lights are reported as diffuse contributions in `record`.
+    fn shade_bsdf(
+        &self,
+        scene: &ObjectStore,
+        ray: &Ray,
+        hit: &Hit,
+        material: &Material,
+        level: u32,
+        mut record: Option<&mut RayTrace>,
+    ) -> Color {
+        let pos = ray.at(hit.distance);
+        let view = -ray.direction;
+        let normal = hit.normal;
+
+        let material = match hit.color {
+            Some(color) => material.with_vertex_color(color),
+            None => *material,
+        };
+
+        let mut color = material.emission();
+
+        for (index, light) in scene.lights().iter().enumerate() {
+            let light_ray = light.lightray(pos);
+            let reflected = material.eval(normal, view, light_ray);
+            if reflected == Color::default() || !self.counters.shadow() {
+                continue;
+            }
+
+            let shadow_ray = Ray::spawn(pos, normal, light_ray, ray.time);
+            let brightness = scene.light_brightness(light, &shadow_ray);
+            let diffuse =
+                reflected * (brightness * PI) * self.light_transmittance(scene, &shadow_ray, light);
+            color += diffuse;
+
+            if let Some(record) = record.as_deref_mut() {
+                record.lights.push(LightContribution {
+                    light: index,
+                    brightness,
+                    diffuse,
+                    specular: Color::default(),
+                });
+            }
+        }
+
+        if level < self.max_level && material.glossy() {
+            color += self.trace_glossy(scene, ray, hit, level + 1, record, |u| {
+                material.scatter(normal, view, u)
+            });
+        } else if level < self.max_level {
+            let mut rng = ray_rng(ray, Sampling::Scatter);
+            let scatter = material.scatter(normal, view, Vec2::new(rng.gen(), rng.gen()));
+
+            for (kind, sample) in [
+                (RayKind::Reflection, scatter.reflection),
+                (RayKind::Transmission, scatter.transmission),
+            ] {
+                let Some(sample) = sample else {
+                    continue;
+                };
+
+                let scattered = self.trace_child(
+                    scene,
+                    kind,
+                    &Ray::spawn(pos, normal, sample.direction, ray.time),
+                    level + 1,
+                    record.as_deref_mut(),
+                );
+                color += scattered * sample.weight;
+            }
         }
 
         color
     }
 
+    /// Attenuates `color`, arriving at the origin of `ray` from `distance` along it, by the
+    /// volumes in between and adds the light they scatter towards the origin. Light from the
+    /// scene's lights is scattered once, at points marched along the ray in
+    /// [`Camera::volume_steps`] jittered steps per volume.
+    fn through_volumes(
+        &self,
+        scene: &ObjectStore,
+        ray: &Ray,
+        distance: f32,
+        color: Color,
+    ) -> Color {
+        let volumes = scene.volumes();
+        if volumes.is_empty() {
+            return color;
+        }
+
+        let mut color = color * medium::transmittance(volumes, ray, distance);
+        let steps = self.volume_steps.max(1);
+        let mut rng = ray_rng(ray, Sampling::Volume);
+
+        for volume in volumes {
+            let Some((near, far)) = volume.march_interval(ray, distance) else {
+                continue;
+            };
+            let step = (far - near) / steps as f32;
+            let scattered = volume.medium.scattered_over(step);
+
+            for i in 0..steps {
+                let start = near + step * i as f32;
+                let pos = ray.at(start + step * rng.gen::<f32>());
+
+                let mut incoming = Color::default();
+                for light in scene.lights() {
+                    if !self.counters.shadow() {
+                        continue;
+                    }
+
+                    let light_ray = light.lightray(pos);
+                    let shadow_ray = Ray::new(pos, light_ray, ray.time);
+                    let brightness = scene.light_brightness(light, &shadow_ray);
+                    if brightness == 0. {
+                        continue;
+                    }
+
+                    // the light travels along -light_ray and is scattered back along the ray
+                    let phase = volume.medium.phase(light_ray.dot(ray.direction));
+                    incoming += self.light_transmittance(scene, &shadow_ray, light)
+                        * (brightness * phase * PI);
+                }
+
+                color += incoming * scattered * medium::transmittance(volumes, ray, start);
+            }
+        }
+
+        color
+    }
+
+    /// Share of the light of `light` arriving at the origin of `shadow_ray` through the volumes
+    /// in between.
+    fn light_transmittance(&self, scene: &ObjectStore, shadow_ray: &Ray, light: &Light) -> Color {
+        let distance = (light.position - shadow_ray.origin).length();
+        medium::transmittance(scene.volumes(), shadow_ray, distance)
+    }
+
     pub fn frame_buffer(&self) -> &[Color] {
         &self.frame_buffer
     }
+
+    /// Returns the object and material seen through pixel `(x, y)` in the last rendered frame.
+    pub fn ids_at(&self, x: u32, y: u32) -> Option<(ObjectId, MaterialId)> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.id_buffer
+            .get((y * self.width + x) as usize)
+            .copied()
+            .flatten()
+    }
+}
+
+/// What random numbers drawn by [`ray_rng`] are used for.
+#[derive(Debug, Clone, Copy)]
+enum Sampling {
+    Glossy = 1,
+    AmbientOcclusion,
+    Scatter,
+    Volume,
+}
+
+/// Random numbers for sampling at the hit of `ray`, seeded by the ray so that renders are
+/// reproducible. Each use gets its own sequence, so that for instance the ambient occlusion
+/// probes of a hit don't follow the directions of its glossy rays.
+fn ray_rng(ray: &Ray, sampling: Sampling) -> SmallRng {
+    let seed = [ray.origin, ray.direction]
+        .iter()
+        .flat_map(|v| v.to_array())
+        .map(f32::to_bits)
+        .chain([sampling as u32])
+        .fold(0xcbf29ce484222325, |seed: u64, bits| {
+            (seed ^ bits as u64).wrapping_mul(0x100000001b3)
+        });
+    SmallRng::seed_from_u64(seed)
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -218,7 +807,340 @@ pub struct CameraDescriptor {
     pub width: u32,
     pub height: u32,
 
+    pub projection: Projection,
+
+    /// Time at which the shutter opens; single-sampled renders are taken at this instant.
+    pub shutter_open: f32,
+    pub shutter_close: f32,
+    /// Pose of the camera at `shutter_close`, or `None` for a static camera.
+    pub motion: Option<CameraMotion>,
+
+    /// Number of jittered rays traced per pixel, each at a random time within the shutter interval.
+    pub samples: u32,
+    /// Number of rays averaged over the lobe of a glossy reflection or transmission at the first
+    /// bounce; deeper bounces follow a single ray.
+    pub glossy_samples: u32,
+    /// Number of steps marched through each volume a ray crosses to gather the light it scatters.
+    pub volume_steps: u32,
+
+    /// Deepest reflection or transmission followed from a primary ray.
     pub max_level: u32,
+    /// Most secondary and shadow rays traced on behalf of each primary ray, or `None` for no
+    /// limit. Rays beyond the budget are skipped and contribute no light.
+    pub ray_budget: Option<u32>,
 
     pub background: Color,
+
+    /// Darkens the ambient term of Phong materials where nearby geometry blocks it, or `None`
+    /// for a constant ambient term.
+    pub ambient_occlusion: Option<AmbientOcclusion>,
+    pub render_mode: RenderMode,
+}
+
+/// Sampling of ambient occlusion: the share of a hemisphere of rays around the normal that
+/// escapes without hitting anything within `radius`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmbientOcclusion {
+    /// Number of rays cast per shaded point.
+    pub samples: u32,
+    /// Distance within which geometry occludes.
+    pub radius: f32,
+}
+
+impl Default for AmbientOcclusion {
+    fn default() -> Self {
+        AmbientOcclusion {
+            samples: 16,
+            radius: 1.,
+        }
+    }
+}
+
+/// Progress of a render, reported after every row of pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderProgress {
+    pub rows_done: u32,
+    pub rows: u32,
+    /// Time spent tracing so far.
+    pub elapsed: Duration,
+}
+
+impl RenderProgress {
+    /// Share of the frame rendered, between 0 and 1.
+    pub fn fraction(&self) -> f32 {
+        self.rows_done as f32 / self.rows.max(1) as f32
+    }
+
+    /// Estimates the time left, assuming the remaining rows take as long as the ones done.
+    pub fn remaining(&self) -> Duration {
+        if self.rows_done == 0 {
+            return Duration::ZERO;
+        }
+        self.elapsed
+            .mul_f64((self.rows - self.rows_done) as f64 / self.rows_done as f64)
+    }
+}
+
+/// What [`Camera::trace`] renders into the frame buffer, and what [`Camera::radiance`] and
+/// [`Camera::inspect`] report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RenderMode {
+    /// The fully shaded scene.
+    #[default]
+    Shaded,
+    /// The ambient occlusion of the primary hits in grey, white where nothing is hit. Uses the
+    /// camera's ambient occlusion settings, or the defaults if it has none.
+    AmbientOcclusion,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CameraMotion {
+    pub eye_pointer: Vec3,
+    pub look_pointer: Vec3,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct View {
+    eye: Vec3,
+    first_ray: Vec3,
+    gaze: Vec3,
+    right: Vec3,
+    up: Vec3,
+    scrnx: Vec3,
+    scrny: Vec3,
+}
+
+/// Maps pixel coordinates on the image plane to primary rays.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Projection {
+    /// Pinhole projection using the camera's vertical field of view.
+    #[default]
+    Perspective,
+    /// Parallel rays along the gaze direction, covering `height` world units vertically.
+    Orthographic { height: f32 },
+    /// Equidistant fisheye covering `fov` degrees across the image circle.
+    Fisheye { fov: f32 },
+    /// Full 360° × 180° latitude/longitude panorama centered on the gaze direction.
+    Equirectangular,
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::vec3;
+
+    use super::*;
+    use crate::{
+        light::Light,
+        object::{cuboid::Cuboid, plane::Plane, sphere::Sphere},
+        surface::SurfaceMaterial,
+    };
+
+    /// Camera at z = 5 looking at the origin, with the y axis up.
+    fn camera(projection: Projection, width: u32, height: u32) -> Camera {
+        let mut camera = Camera::new(&CameraDescriptor {
+            eye_pointer: vec3(0., 0., 5.),
+            look_pointer: Vec3::ZERO,
+            up_pointer: Vec3::Y,
+            vfov: 60.,
+            width,
+            height,
+            projection,
+            ..Default::default()
+        });
+        camera.calculate_first_ray();
+        camera
+    }
+
+    #[test]
+    fn center_pixel_looks_along_the_gaze() {
+        for projection in [
+            Projection::Perspective,
+            Projection::Orthographic { height: 2. },
+            Projection::Fisheye { fov: 180. },
+            Projection::Equirectangular,
+        ] {
+            let camera = camera(projection, 200, 100);
+            let ray = camera.primary_ray(100., 50., 0.).unwrap();
+            assert!(
+                ray.direction.abs_diff_eq(Vec3::NEG_Z, 1e-5),
+                "{projection:?} looks along {}",
+                ray.direction
+            );
+        }
+    }
+
+    #[test]
+    fn fisheye_is_cut_to_its_image_circle() {
+        let camera = camera(Projection::Fisheye { fov: 180. }, 200, 100);
+
+        assert!(camera.primary_ray(0.5, 0.5, 0.).is_none());
+        assert!(camera.primary_ray(10., 50., 0.).is_none());
+        // the rim of the circle looks sideways
+        let rim = camera.primary_ray(150., 50., 0.).unwrap();
+        assert!(
+            rim.direction.abs_diff_eq(Vec3::X, 1e-5),
+            "{}",
+            rim.direction
+        );
+    }
+
+    #[test]
+    fn equirectangular_corners_reach_the_poles_and_the_back() {
+        let (width, height) = (360, 180);
+        let camera = camera(Projection::Equirectangular, width, height);
+        let angles = |x: f32, y: f32| {
+            let direction = camera.primary_ray(x, y, 0.).unwrap().direction;
+            // longitude from the gaze towards the right, latitude towards the top
+            (
+                direction.x.atan2(-direction.z),
+                direction.y.clamp(-1., 1.).asin(),
+            )
+        };
+        let (w, h) = (width as f32, height as f32);
+
+        for (y, latitude) in [(0., PI / 2.), (h, -PI / 2.)] {
+            for x in [0., w] {
+                let (_, lat) = angles(x, y);
+                assert!((lat - latitude).abs() < 1e-3, "{lat} at ({x}, {y})");
+            }
+        }
+        // the longitude is meaningless at the poles, so it is checked across the middle row;
+        // the centers of the edge pixels are half a pixel short of the back
+        for (x, longitude) in [(0.5, -PI + PI / w), (w - 0.5, PI - PI / w)] {
+            let (lon, _) = angles(x, h / 2.);
+            assert!((lon - longitude).abs() < 1e-3, "{lon} at {x}");
+        }
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let camera = camera(Projection::Orthographic { height: 2. }, 200, 100);
+
+        let corners = [(0., 0.), (200., 0.), (0., 100.), (200., 100.)]
+            .map(|(x, y)| camera.primary_ray(x, y, 0.).unwrap());
+        for ray in &corners {
+            assert!(ray.direction.abs_diff_eq(Vec3::NEG_Z, 1e-6));
+            assert!((ray.origin.z - 5.).abs() < 1e-6);
+        }
+        // the view plane is 2 units high and as wide as the image's aspect ratio
+        assert!(corners[0].origin.abs_diff_eq(vec3(-2., 1., 5.), 1e-5));
+        assert!(corners[3].origin.abs_diff_eq(vec3(2., -1., 5.), 1e-5));
+    }
+
+    #[test]
+    fn inspect_records_hits_lights_and_reflections() {
+        // a reflective plane facing the camera, which sees a sphere behind the camera in it
+        let mut scene = ObjectStore::new();
+        let mirror = scene.add_material(
+            "mirror",
+            SurfaceMaterial {
+                diffuse: Color::new(0.5, 0.5, 0.5),
+                reflection: 1.,
+                ..Default::default()
+            },
+        );
+        let white = scene.add_material("white", SurfaceMaterial::default());
+        let plane = scene.add_object(Plane::new(Vec3::ZERO, Vec3::Z, mirror));
+        let sphere = scene.add_object(Sphere::new(vec3(0., 0., 8.), 1., white));
+        scene.add_light(Light::new(vec3(3., 0., 3.), 1.));
+
+        // odd sizes put the center of the middle pixel on the gaze
+        let mut camera = camera(Projection::Perspective, 21, 11);
+        camera.max_level = 2;
+
+        let trace = camera.inspect(&scene, 10, 5).unwrap();
+        assert_eq!(trace.kind, RayKind::Primary);
+        let hit = trace.hit.as_ref().unwrap();
+        assert_eq!(hit.object, plane);
+        assert_eq!(hit.material_name, "mirror");
+        assert!((hit.distance - 5.).abs() < 1e-4, "{}", hit.distance);
+
+        assert_eq!(trace.lights.len(), 1);
+        assert!(trace.lights[0].brightness > 0.);
+        assert!(trace.lights[0].diffuse.r() > 0.);
+
+        assert_eq!(trace.children.len(), 1);
+        let reflection = &trace.children[0];
+        assert_eq!(reflection.kind, RayKind::Reflection);
+        assert_eq!(reflection.hit.as_ref().unwrap().object, sphere);
+        assert!(trace.color.r() >= trace.lights[0].diffuse.r());
+
+        let printed = trace.to_string();
+        assert!(printed.contains("with material 0 \"mirror\""), "{printed}");
+        assert!(printed.contains("  Reflection ray from"), "{printed}");
+
+        // clicks outside the frame aren't pixels
+        assert!(camera.inspect(&scene, 21, 5).is_none());
+        assert!(camera.inspect(&scene, 10, 11).is_none());
+    }
+
+    #[test]
+    fn progress_estimate_counts_down() {
+        // rows taking equally long leave a steadily shrinking estimate
+        let progress = (0..=10).map(|rows_done| RenderProgress {
+            rows_done,
+            rows: 10,
+            elapsed: Duration::from_millis(7) * rows_done,
+        });
+        let (fractions, remaining): (Vec<_>, Vec<_>) = progress
+            .map(|progress| (progress.fraction(), progress.remaining()))
+            .unzip();
+
+        assert!(fractions.windows(2).all(|pair| pair[0] < pair[1]));
+        // nothing can be estimated before the first row, which the render never reports
+        assert!(remaining[1..].windows(2).all(|pair| pair[0] > pair[1]));
+        assert_eq!(fractions[10], 1.);
+        assert_eq!(remaining[10], Duration::ZERO);
+        assert_eq!(remaining[1], Duration::from_millis(63));
+    }
+
+    fn ambient_occlusion_camera() -> Camera {
+        Camera::new(&CameraDescriptor {
+            width: 1,
+            height: 1,
+            ambient_occlusion: Some(AmbientOcclusion {
+                samples: 32,
+                radius: 2.,
+            }),
+            render_mode: RenderMode::AmbientOcclusion,
+            ..Default::default()
+        })
+    }
+
+    /// Ambient visibility of the first hit of `ray`, through the camera's render mode.
+    fn visibility(scene: &ObjectStore, ray: &Ray) -> f32 {
+        let camera = ambient_occlusion_camera();
+        let hit = scene.intersect(ray).map(|(_, hit)| hit);
+        camera.radiance(scene, ray, hit.as_ref()).r()
+    }
+
+    #[test]
+    fn open_plane_is_unoccluded() {
+        let mut scene = ObjectStore::new();
+        let material = scene.add_material("white", SurfaceMaterial::default());
+        scene.add_object(Plane::new(Vec3::ZERO, Vec3::Y, material));
+
+        let ray = Ray::new(vec3(0.3, 1., 0.2), vec3(0.1, -1., 0.).normalize(), 0.);
+        assert_eq!(visibility(&scene, &ray), 1.);
+
+        // nothing to occlude the background either
+        let up = Ray::new(Vec3::Y, Vec3::Y, 0.);
+        assert_eq!(visibility(&scene, &up), 1.);
+    }
+
+    #[test]
+    fn closed_box_within_the_radius_is_occluded() {
+        let mut scene = ObjectStore::new();
+        let material = scene.add_material("white", SurfaceMaterial::default());
+        scene.add_object(Cuboid::new(Vec3::splat(-0.5), Vec3::splat(0.5), material));
+
+        let ray = Ray::new(Vec3::ZERO, vec3(0.2, -1., 0.1).normalize(), 0.);
+        assert_eq!(visibility(&scene, &ray), 0.);
+
+        // the render mode also applies to inspected rays
+        let camera = ambient_occlusion_camera();
+        let mut trace = RayTrace::new(RayKind::Primary, ray);
+        camera.trace_ray(&scene, &ray, 0, Some(&mut trace));
+        assert_eq!(trace.color.r(), 0.);
+    }
 }
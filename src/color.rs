@@ -50,6 +50,15 @@ impl Mul<f32> for Color {
     }
 }
 
+/// Component-wise product, filtering one color by another.
+impl Mul<Color> for Color {
+    type Output = Self;
+
+    fn mul(self, rhs: Color) -> Self::Output {
+        Color(self.0 * rhs.0)
+    }
+}
+
 impl MulAssign<f32> for Color {
     fn mul_assign(&mut self, rhs: f32) {
         self.0 *= rhs;
@@ -61,3 +70,15 @@ impl From<[f32; 3]> for Color {
         Color(Vec3::new(value[0], value[1], value[2]))
     }
 }
+
+impl From<Vec3> for Color {
+    fn from(value: Vec3) -> Self {
+        Color(value)
+    }
+}
+
+impl From<Color> for Vec3 {
+    fn from(value: Color) -> Self {
+        value.0
+    }
+}
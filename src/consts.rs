@@ -1 +1,6 @@
 pub const FAR_AWAY: f32 = 1e20;
+
+/// Hits nearer to the ray's origin than this are ignored, so that a ray doesn't hit the point it
+/// starts at. Secondary rays are moved off their surface by [`Ray::spawn`](crate::ray::Ray::spawn)
+/// as well.
+pub const MIN_HIT_DISTANCE: f32 = f32::EPSILON;
@@ -0,0 +1,154 @@
+pub mod gltf;
+pub mod obj;
+pub mod ply;
+pub mod stl;
+
+use eyre::{bail, Result};
+use glam::{Affine3A, Vec2, Vec3};
+
+use crate::{color::Color, object::mesh::Mesh, object_store::MaterialId, surface::SurfaceMaterial};
+
+/// Options of the PLY and STL importers, which load a single mesh without materials.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshOptions {
+    /// Placement of the mesh in the scene.
+    pub transform: Affine3A,
+    /// Material of the mesh; vertex colors take the place of its diffuse color.
+    pub default_material: SurfaceMaterial,
+}
+
+impl Default for MeshOptions {
+    fn default() -> Self {
+        MeshOptions {
+            transform: Affine3A::IDENTITY,
+            default_material: default_material(),
+        }
+    }
+}
+
+/// Light grey diffuse material given to imported geometry without a material of its own.
+fn default_material() -> SurfaceMaterial {
+    SurfaceMaterial {
+        diffuse: Color::new(0.8, 0.8, 0.8),
+        ..Default::default()
+    }
+}
+
+/// Builds an imported triangle mesh, rejecting meshes with out of range indices. Degenerate
+/// triangles have no surface to hit and are dropped with a warning, as are normals and texture
+/// coordinates that don't match the vertices one to one.
+fn validated_mesh(
+    name: &str,
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<Vec2>,
+    triangles: Vec<[u32; 3]>,
+    material: MaterialId,
+) -> Result<Mesh> {
+    let normals = per_vertex(name, "normals", normals, positions.len());
+    let uvs = per_vertex(name, "texture coordinates", uvs, positions.len());
+
+    let count = triangles.len();
+    let mut kept = Vec::with_capacity(count);
+    for (i, triangle) in triangles.into_iter().enumerate() {
+        let [Some(a), Some(b), Some(c)] =
+            triangle.map(|index| positions.get(index as usize).copied())
+        else {
+            bail!("triangle {i} of mesh {name:?} references a missing vertex");
+        };
+
+        let normal = (b - a).cross(c - a);
+        if normal.length() > f32::EPSILON * (b - a).length() * (c - a).length() {
+            kept.push(triangle);
+        }
+    }
+
+    if kept.len() < count {
+        log::warn!(
+            "dropped {} degenerate triangles of mesh {name:?}",
+            count - kept.len()
+        );
+    }
+
+    Ok(Mesh::new(positions, normals, uvs, kept, material))
+}
+
+/// Returns `attribute` if it has one value per vertex, and drops it with a warning otherwise.
+fn per_vertex<T>(name: &str, attribute: &str, values: Vec<T>, vertices: usize) -> Vec<T> {
+    if values.is_empty() || values.len() == vertices {
+        return values;
+    }
+
+    log::warn!(
+        "dropped the {attribute} of mesh {name:?}: {} values for {vertices} vertices",
+        values.len()
+    );
+    Vec::new()
+}
+
+/// Directory of fixture files for a single test, removed again when dropped.
+#[cfg(test)]
+pub(crate) struct TestDir(std::path::PathBuf);
+
+#[cfg(test)]
+impl TestDir {
+    /// Creates an empty directory named after `name`, unique to this test run.
+    pub fn new(name: &str) -> Self {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        static COUNT: AtomicU32 = AtomicU32::new(0);
+        let directory = std::env::temp_dir().join(format!(
+            "rust_tracer_{name}_{}_{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&directory).unwrap();
+        TestDir(directory)
+    }
+
+    /// Writes `contents` to `file` in the directory and returns its path.
+    pub fn write(&self, file: &str, contents: impl AsRef<[u8]>) -> std::path::PathBuf {
+        let path = self.0.join(file);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_degenerate_triangles() {
+        let positions = vec![Vec3::ZERO, Vec3::X, Vec3::Y, Vec3::X * 2.];
+        let triangles = vec![[0, 1, 2], [0, 1, 3], [2, 2, 1]];
+
+        let mesh = validated_mesh(
+            "test",
+            positions.clone(),
+            Vec::new(),
+            Vec::new(),
+            triangles,
+            MaterialId(0),
+        )
+        .unwrap();
+        assert_eq!(mesh.triangles(), [[0, 1, 2]]);
+
+        let missing = validated_mesh(
+            "test",
+            positions,
+            Vec::new(),
+            Vec::new(),
+            vec![[0, 1, 2], [0, 1, 4]],
+            MaterialId(0),
+        );
+        assert!(format!("{}", missing.unwrap_err()).contains("triangle 1"));
+    }
+}
@@ -0,0 +1,455 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use eyre::{Result, WrapErr};
+use glam::{Affine3A, Mat4, Vec2, Vec3};
+use gltf::{
+    camera::Projection as GltfProjection, khr_lights_punctual::Kind, material::AlphaMode,
+    mesh::Mode, Node,
+};
+
+use crate::{
+    camera::{CameraDescriptor, Projection, RenderMode},
+    color::Color,
+    consts::FAR_AWAY,
+    light::Light,
+    material::{pbr::PbrMaterial, Material},
+    object::{group::Group, instance::Instance, Object},
+    object_store::{MaterialId, ObjectId, ObjectStore},
+    surface::SurfaceMaterial,
+};
+
+use super::validated_mesh;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GltfOptions {
+    /// Placement of the scene's root nodes.
+    pub transform: Affine3A,
+    /// Image size of the imported cameras.
+    pub width: u32,
+    pub height: u32,
+    /// Material of primitives that don't reference one.
+    pub default_material: Material,
+    /// Factor converting light intensities in candela (lux for directional lights) into light
+    /// brightness.
+    pub light_scale: f32,
+}
+
+impl Default for GltfOptions {
+    fn default() -> Self {
+        GltfOptions {
+            transform: Affine3A::IDENTITY,
+            width: 800,
+            height: 800,
+            default_material: PbrMaterial::default().into(),
+            light_scale: 1.,
+        }
+    }
+}
+
+/// Objects and cameras brought in by [`load`]; lights are added to the store directly.
+#[derive(Debug, Clone, Default)]
+pub struct GltfScene {
+    pub objects: Vec<ObjectId>,
+    pub cameras: Vec<CameraDescriptor>,
+}
+
+/// Loads the default scene of the glTF or GLB file at `path` into `store`. Every node with a mesh
+/// becomes an [`Instance`] of that mesh placed with the node's world transform, so meshes
+/// referenced by several nodes are shared.
+///
+/// Lights from `KHR_lights_punctual` become point lights: spot lights lose their cone and
+/// directional lights are placed far away against their direction. Light colors scale the
+/// brightness by their luminance and lose their hue. Textures are ignored; spot lights, colored
+/// lights, textured materials and cameras whose aspect ratio differs from the image size are
+/// reported as warnings.
+pub fn load(
+    store: &mut ObjectStore,
+    path: impl AsRef<Path>,
+    options: &GltfOptions,
+) -> Result<GltfScene> {
+    let path = path.as_ref();
+    let (document, buffers, _) =
+        gltf::import(path).wrap_err_with(|| format!("loading {}", path.display()))?;
+
+    let mut importer = Importer {
+        store,
+        options,
+        buffers: &buffers,
+        materials: HashMap::new(),
+        meshes: HashMap::new(),
+        scene: GltfScene::default(),
+    };
+
+    let scene = document
+        .default_scene()
+        .or_else(|| document.scenes().next());
+    for node in scene.iter().flat_map(|scene| scene.nodes()) {
+        importer
+            .node(&node, options.transform)
+            .wrap_err_with(|| format!("loading {}", path.display()))?;
+    }
+
+    Ok(importer.scene)
+}
+
+struct Importer<'a> {
+    store: &'a mut ObjectStore,
+    options: &'a GltfOptions,
+    buffers: &'a [gltf::buffer::Data],
+    /// Store handles of the document's materials, `None` for the default material.
+    materials: HashMap<Option<usize>, MaterialId>,
+    meshes: HashMap<usize, Arc<Object>>,
+    scene: GltfScene,
+}
+
+impl Importer<'_> {
+    fn node(&mut self, node: &Node, parent: Affine3A) -> Result<()> {
+        let transform =
+            parent * Affine3A::from_mat4(Mat4::from_cols_array_2d(&node.transform().matrix()));
+
+        if let Some(mesh) = node.mesh() {
+            let geometry = match self.meshes.get(&mesh.index()) {
+                Some(geometry) => geometry.clone(),
+                None => {
+                    let geometry = Arc::new(self.mesh(&mesh)?);
+                    self.meshes.insert(mesh.index(), geometry.clone());
+                    geometry
+                }
+            };
+
+            let object = self.store.add_object(Instance::new(geometry, transform));
+            self.scene.objects.push(object);
+        }
+
+        if let Some(camera) = node.camera() {
+            self.scene.cameras.push(self.camera(&camera, transform));
+        }
+
+        if let Some(light) = node.light() {
+            let name = light.name().unwrap_or("unnamed");
+            let [r, g, b] = light.color();
+            if r != g || g != b {
+                log::warn!("light {name:?} is imported as white, keeping only its luminance");
+            }
+            let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+            let brightness = light.intensity() * luminance * self.options.light_scale;
+            let position = match light.kind() {
+                // directional lights shine along the node's -Z axis
+                Kind::Directional => transform.transform_vector3(Vec3::Z).normalize() * FAR_AWAY,
+                Kind::Point => transform.translation.into(),
+                Kind::Spot { .. } => {
+                    log::warn!(
+                        "spot light {name:?} is imported as a point light, without its cone"
+                    );
+                    transform.translation.into()
+                }
+            };
+            self.store.add_light(Light::new(position, brightness));
+        }
+
+        for child in node.children() {
+            self.node(&child, transform)?;
+        }
+
+        Ok(())
+    }
+
+    /// Converts the triangle primitives of a mesh into a single object, grouping them if there
+    /// are several. Points and lines are skipped.
+    fn mesh(&mut self, mesh: &gltf::Mesh) -> Result<Object> {
+        let name = mesh.name().unwrap_or("unnamed");
+        let buffers = self.buffers;
+        let mut primitives = Vec::new();
+
+        for primitive in mesh.primitives() {
+            if !matches!(primitive.mode(), Mode::Triangles) {
+                continue;
+            }
+
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+
+            let positions = reader
+                .read_positions()
+                .map(|positions| positions.map(Vec3::from).collect::<Vec<_>>())
+                .unwrap_or_default();
+            let normals = reader
+                .read_normals()
+                .map(|normals| normals.map(Vec3::from).collect())
+                .unwrap_or_default();
+            let uvs = reader
+                .read_tex_coords(0)
+                .map(|uvs| uvs.into_f32().map(Vec2::from).collect())
+                .unwrap_or_default();
+
+            let indices = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..positions.len() as u32).collect::<Vec<_>>(),
+            };
+            let triangles = indices
+                .chunks_exact(3)
+                .map(|i| [i[0], i[1], i[2]])
+                .collect();
+
+            let material = self.material(&primitive.material());
+            primitives
+                .push(validated_mesh(name, positions, normals, uvs, triangles, material)?.into());
+        }
+
+        Ok(match primitives.len() {
+            1 => primitives.remove(0),
+            _ => Group::new(primitives).into(),
+        })
+    }
+
+    /// Converts a metallic-roughness material. Blended materials, which the PBR model can't
+    /// represent, are approximated by a Phong material transmitting the uncovered part.
+    fn material(&mut self, material: &gltf::Material) -> MaterialId {
+        let index = material.index();
+        if let Some(id) = self.materials.get(&index) {
+            return *id;
+        }
+
+        let name = material.name().unwrap_or("default");
+        let surface = match index {
+            Some(_) => {
+                let pbr = material.pbr_metallic_roughness();
+                let textures = [
+                    ("base color", pbr.base_color_texture().is_some()),
+                    (
+                        "metallic-roughness",
+                        pbr.metallic_roughness_texture().is_some(),
+                    ),
+                    ("normal", material.normal_texture().is_some()),
+                    ("occlusion", material.occlusion_texture().is_some()),
+                    ("emissive", material.emissive_texture().is_some()),
+                ]
+                .iter()
+                .filter(|(_, used)| *used)
+                .map(|(texture, _)| *texture)
+                .collect::<Vec<_>>();
+                if !textures.is_empty() {
+                    log::warn!(
+                        "ignoring the {} textures of material {name:?}, only its factors are used",
+                        textures.join(", ")
+                    );
+                }
+
+                let [r, g, b, alpha] = pbr.base_color_factor();
+                let pbr = PbrMaterial {
+                    base_color: Color::new(r, g, b),
+                    metallic: pbr.metallic_factor(),
+                    roughness: pbr.roughness_factor(),
+                    emission: material.emissive_factor().into(),
+                };
+
+                match material.alpha_mode() {
+                    AlphaMode::Blend if alpha < 1. => blended(&pbr, alpha).into(),
+                    _ => pbr.into(),
+                }
+            }
+            None => self.options.default_material,
+        };
+
+        let id = self.store.add_material(name, surface);
+        self.materials.insert(index, id);
+        id
+    }
+
+    fn camera(&self, camera: &gltf::Camera, transform: Affine3A) -> CameraDescriptor {
+        let eye: Vec3 = transform.translation.into();
+        let gaze = transform.transform_vector3(Vec3::NEG_Z).normalize();
+        let up = transform.transform_vector3(Vec3::Y).normalize();
+
+        let (projection, vfov, near, far) = match camera.projection() {
+            GltfProjection::Perspective(perspective) => {
+                let aspect = self.options.width as f32 / self.options.height as f32;
+                if let Some(ratio) = perspective
+                    .aspect_ratio()
+                    .filter(|ratio| (ratio - aspect).abs() > 1e-3 * aspect)
+                {
+                    log::warn!(
+                        "camera {:?} has aspect ratio {ratio}, not that of the {}x{} image",
+                        camera.name().unwrap_or("unnamed"),
+                        self.options.width,
+                        self.options.height
+                    );
+                }
+                (
+                    Projection::Perspective,
+                    perspective.yfov().to_degrees(),
+                    perspective.znear(),
+                    perspective.zfar().unwrap_or(FAR_AWAY),
+                )
+            }
+            GltfProjection::Orthographic(orthographic) => (
+                Projection::Orthographic {
+                    height: 2. * orthographic.ymag(),
+                },
+                50.,
+                orthographic.znear(),
+                orthographic.zfar(),
+            ),
+        };
+
+        CameraDescriptor {
+            eye_pointer: eye,
+            look_pointer: eye + gaze,
+            up_pointer: up,
+            near,
+            far,
+            vfov,
+            width: self.options.width,
+            height: self.options.height,
+            projection,
+            shutter_open: 0.,
+            shutter_close: 1.,
+            motion: None,
+            samples: 1,
+            glossy_samples: 1,
+            volume_steps: 16,
+            max_level: 5,
+            ray_budget: None,
+            background: Color::default(),
+            ambient_occlusion: None,
+            render_mode: RenderMode::Shaded,
+        }
+    }
+}
+
+/// Phong approximation of a PBR material covering `alpha` of the surface: metals tint their
+/// specular highlight and reflect, rough surfaces get broad highlights and blurred reflections.
+fn blended(pbr: &PbrMaterial, alpha: f32) -> SurfaceMaterial {
+    let base_color: Vec3 = pbr.base_color.into();
+    let specular = Vec3::splat(0.04).lerp(base_color, pbr.metallic);
+    let alpha_roughness = (pbr.roughness * pbr.roughness).max(1e-3);
+
+    SurfaceMaterial {
+        ambient: pbr.emission,
+        diffuse: (base_color * (1. - pbr.metallic)).into(),
+        specular: specular.into(),
+        specular_power: (2. / (alpha_roughness * alpha_roughness) - 2.).min(1e4),
+        reflection: pbr.metallic,
+        transparency: 1. - alpha,
+        roughness: pbr.roughness,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{import::TestDir, ray::Ray};
+
+    /// A red triangle under a translated parent node, a camera and a point light.
+    const GLTF: &str = r#"{
+        "asset": { "version": "2.0" },
+        "extensionsUsed": ["KHR_lights_punctual"],
+        "extensions": {
+            "KHR_lights_punctual": { "lights": [{ "type": "point", "intensity": 2 }] }
+        },
+        "scene": 0,
+        "scenes": [{ "nodes": [0, 2, 3] }],
+        "nodes": [
+            { "translation": [0, 0, -5], "children": [1] },
+            { "mesh": 0 },
+            { "camera": 0, "translation": [0, 0, 1] },
+            { "translation": [0, 3, 0], "extensions": { "KHR_lights_punctual": { "light": 0 } } }
+        ],
+        "cameras": [{ "type": "perspective", "perspective": { "yfov": 1.0, "znear": 0.1 } }],
+        "materials": [{
+            "name": "red",
+            "pbrMetallicRoughness": { "baseColorFactor": [1, 0, 0, 1], "metallicFactor": 0 }
+        }],
+        "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 }, "material": 0 }] }],
+        "buffers": [{ "uri": "triangle.bin", "byteLength": 36 }],
+        "bufferViews": [{ "buffer": 0, "byteLength": 36 }],
+        "accessors": [{
+            "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+            "min": [-1, -1, 0], "max": [1, 1, 0]
+        }]
+    }"#;
+
+    /// Writes the triangle scene and its buffer to `directory`, returning the scene's path.
+    fn write_files(directory: &TestDir) -> std::path::PathBuf {
+        let positions: [f32; 9] = [-1., -1., 0., 1., -1., 0., 0., 1., 0.];
+        let bytes: Vec<u8> = positions.iter().flat_map(|p| p.to_le_bytes()).collect();
+        directory.write("triangle.bin", bytes);
+        directory.write("triangle.gltf", GLTF)
+    }
+
+    #[test]
+    fn loads_nodes_materials_cameras_and_lights() {
+        let directory = TestDir::new("gltf");
+        let mut store = ObjectStore::new();
+        let scene = load(&mut store, write_files(&directory), &GltfOptions::default()).unwrap();
+
+        assert_eq!(scene.objects.len(), 1);
+        let (object, hit) = store
+            .intersect(&Ray::new(Vec3::ZERO, Vec3::NEG_Z, 0.))
+            .unwrap();
+        assert_eq!(object, scene.objects[0]);
+        assert!((hit.distance - 5.).abs() < 1e-5);
+        assert_eq!(store.material_name(hit.material), "red");
+        let Material::Pbr(material) = store.material(hit.material) else {
+            panic!("expected a PBR material");
+        };
+        assert_eq!(material.base_color, Color::new(1., 0., 0.));
+        assert_eq!(material.metallic, 0.);
+
+        let [camera] = scene.cameras.as_slice() else {
+            panic!("expected one camera");
+        };
+        assert_eq!(camera.eye_pointer, Vec3::new(0., 0., 1.));
+        assert_eq!(camera.look_pointer, Vec3::ZERO);
+        assert!((camera.vfov - 1f32.to_degrees()).abs() < 1e-4);
+
+        let lights = store
+            .lights_mut()
+            .map(|light| light.position)
+            .collect::<Vec<_>>();
+        assert_eq!(lights, [Vec3::new(0., 3., 0.)]);
+    }
+
+    #[test]
+    fn drops_mismatched_normals_and_dims_colored_lights() {
+        let directory = TestDir::new("gltf_mismatch");
+        let path = write_files(&directory);
+        let gltf = GLTF
+            .replace(
+                r#""attributes": { "POSITION": 0 }"#,
+                r#""attributes": { "POSITION": 0, "NORMAL": 1 }"#,
+            )
+            .replace(
+                r#""min": [-1, -1, 0], "max": [1, 1, 0]
+        }]"#,
+                r#""min": [-1, -1, 0], "max": [1, 1, 0]
+        }, { "bufferView": 0, "componentType": 5126, "count": 2, "type": "VEC3" }]"#,
+            )
+            .replace(
+                r#""type": "point", "intensity": 2"#,
+                r#""type": "point", "intensity": 2, "color": [0, 0, 1]"#,
+            );
+        std::fs::write(&path, gltf).unwrap();
+        let mut store = ObjectStore::new();
+        load(&mut store, path, &GltfOptions::default()).unwrap();
+
+        // the two normals don't cover the three vertices, so the face normal is used
+        let (_, hit) = store
+            .intersect(&Ray::new(Vec3::ZERO, Vec3::NEG_Z, 0.))
+            .unwrap();
+        assert!(hit.normal.abs_diff_eq(Vec3::Z, 1e-6), "{}", hit.normal);
+
+        let brightness = store
+            .lights_mut()
+            .map(|light| light.brightness)
+            .collect::<Vec<_>>();
+        assert_eq!(brightness, [2. * 0.0722]);
+    }
+
+    #[test]
+    fn reports_missing_files() {
+        let mut store = ObjectStore::new();
+        let error = load(&mut store, "does/not/exist.gltf", &GltfOptions::default()).unwrap_err();
+
+        assert!(format!("{error}").contains("does/not/exist.gltf"));
+    }
+}
@@ -0,0 +1,321 @@
+use std::{fs::File, io::BufReader, path::Path};
+
+use eyre::{Result, WrapErr};
+use glam::{vec2, vec3, Affine3A, Vec2, Vec3};
+
+use crate::{
+    object_store::{ObjectId, ObjectStore},
+    subdivision::{PolygonMesh, Subdivision},
+    surface::SurfaceMaterial,
+};
+
+use super::{default_material, validated_mesh};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObjOptions {
+    /// Placement of the models in the scene.
+    pub transform: Affine3A,
+    /// Subdivision applied to every model, `None` to triangulate the polygons as modelled.
+    pub subdivision: Option<Subdivision>,
+    /// How polygons with more than three vertices are split into triangles when they aren't
+    /// subdivided.
+    pub triangulation: Triangulation,
+    /// Material of models that don't reference one, and of all models if the MTL files fail to
+    /// load.
+    pub default_material: SurfaceMaterial,
+}
+
+impl Default for ObjOptions {
+    fn default() -> Self {
+        ObjOptions {
+            transform: Affine3A::IDENTITY,
+            subdivision: None,
+            triangulation: Triangulation::default(),
+            default_material: default_material(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Triangulation {
+    /// Fans out from the first vertex of each polygon, which is only right for convex polygons.
+    #[default]
+    Fan,
+    /// Clips ears off each polygon in its plane, which also splits concave polygons correctly.
+    EarClipping,
+}
+
+/// Loads every model of the Wavefront OBJ file at `path` into `store`, along with the materials
+/// of the MTL files it references. MTL paths are resolved relative to the OBJ file. Returns the
+/// handles of the added objects.
+pub fn load(
+    store: &mut ObjectStore,
+    path: impl AsRef<Path>,
+    options: &ObjOptions,
+) -> Result<Vec<ObjectId>> {
+    let path = path.as_ref();
+    let directory = path.parent().unwrap_or(Path::new(""));
+
+    // subdivision needs the polygons of the cage and vertices shared across faces, ear clipping
+    // the polygons with a single index for all vertex attributes
+    let load_options = match (options.subdivision, options.triangulation) {
+        (Some(_), _) => tobj::LoadOptions {
+            ignore_points: true,
+            ignore_lines: true,
+            ..Default::default()
+        },
+        (None, Triangulation::EarClipping) => tobj::LoadOptions {
+            triangulate: false,
+            ..tobj::GPU_LOAD_OPTIONS
+        },
+        (None, Triangulation::Fan) => tobj::GPU_LOAD_OPTIONS,
+    };
+
+    let file = File::open(path).wrap_err_with(|| format!("opening {}", path.display()))?;
+    let (models, materials) =
+        tobj::load_obj_buf(&mut BufReader::new(file), &load_options, |mtl_path| {
+            tobj::load_mtl(directory.join(mtl_path))
+        })
+        .wrap_err_with(|| format!("parsing {}", path.display()))?;
+
+    let materials = match materials {
+        Ok(materials) => materials
+            .iter()
+            .map(|m| {
+                let material = SurfaceMaterial {
+                    ambient: m.ambient.unwrap_or_default().into(),
+                    diffuse: m.diffuse.unwrap_or_default().into(),
+                    specular: m.specular.unwrap_or_default().into(),
+                    specular_power: m.shininess.unwrap_or(0.),
+                    reflection: 0.,
+                    transparency: m.dissolve.unwrap_or(0.),
+                    roughness: 0.,
+                };
+                store.add_material(&m.name, material)
+            })
+            .collect(),
+        Err(error) => {
+            log::warn!(
+                "loading materials of {} failed, using the default material: {error}",
+                path.display()
+            );
+            Vec::new()
+        }
+    };
+
+    let mut default_material = None;
+    let normal_matrix = options.transform.matrix3.inverse().transpose();
+    let mut objects = Vec::with_capacity(models.len());
+
+    for model in models {
+        let mesh = model.mesh;
+
+        let material = match mesh.material_id.and_then(|i| materials.get(i)) {
+            Some(material) => *material,
+            None => *default_material
+                .get_or_insert_with(|| store.add_material("default", options.default_material)),
+        };
+
+        let positions = mesh
+            .positions
+            .chunks(3)
+            .map(|p| options.transform.transform_point3(vec3(p[0], p[1], p[2])))
+            .collect::<Vec<_>>();
+
+        let arities = if mesh.face_arities.is_empty() {
+            vec![3; mesh.indices.len() / 3]
+        } else {
+            mesh.face_arities
+        };
+
+        if let Some(subdivision) = options.subdivision {
+            let cage = PolygonMesh::from_arities(positions, &mesh.indices, &arities);
+            let smooth = cage.subdivide(subdivision);
+            let triangles = smooth.triangles();
+            let normals = smooth.vertex_normals();
+
+            let mesh = validated_mesh(
+                &model.name,
+                smooth.positions,
+                normals,
+                Vec::new(),
+                triangles,
+                material,
+            )
+            .wrap_err_with(|| format!("loading {}", path.display()))?;
+            objects.push(store.add_object(mesh));
+            continue;
+        }
+
+        let normals = mesh
+            .normals
+            .chunks(3)
+            .map(|n| (normal_matrix * vec3(n[0], n[1], n[2])).normalize())
+            .collect();
+
+        let uvs = mesh.texcoords.chunks(2).map(|t| vec2(t[0], t[1])).collect();
+
+        // tobj has already fanned out the polygons unless they are to be ear clipped
+        let mut offset = 0;
+        let mut triangles = Vec::with_capacity(mesh.indices.len() / 3);
+        for &arity in &arities {
+            let face = &mesh.indices[offset..offset + arity as usize];
+            offset += arity as usize;
+            ear_clip(&positions, face, &mut triangles);
+        }
+
+        let mesh = validated_mesh(&model.name, positions, normals, uvs, triangles, material)
+            .wrap_err_with(|| format!("loading {}", path.display()))?;
+        objects.push(store.add_object(mesh));
+    }
+
+    Ok(objects)
+}
+
+/// Splits the polygon `face` into triangles of the same winding, clipping off one convex corner
+/// that contains no other vertex at a time. Polygons that are too degenerate to find such a
+/// corner on fall back to a fan.
+fn ear_clip(positions: &[Vec3], face: &[u32], triangles: &mut Vec<[u32; 3]>) {
+    if face.len() <= 3 {
+        if let [a, b, c] = *face {
+            triangles.push([a, b, c]);
+        }
+        return;
+    }
+
+    // project into the plane of the polygon, oriented so that its winding is counter-clockwise
+    let position = |i: u32| positions.get(i as usize).copied().unwrap_or_default();
+    let normal = (0..face.len())
+        .map(|i| position(face[i]).cross(position(face[(i + 1) % face.len()])))
+        .sum::<Vec3>();
+    let (u, v) = normal.normalize_or_zero().any_orthonormal_pair();
+    let flip = if u.cross(v).dot(normal) < 0. { -1. } else { 1. };
+    let point = |i: u32| {
+        let p = position(i);
+        Vec2::new(p.dot(u), p.dot(v) * flip)
+    };
+    let cross = |a: Vec2, b: Vec2, c: Vec2| (b - a).perp_dot(c - a);
+
+    let mut remaining = face.to_vec();
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let ear = (0..n).find(|&i| {
+            let [a, b, c] = [
+                remaining[(i + n - 1) % n],
+                remaining[i],
+                remaining[(i + 1) % n],
+            ]
+            .map(point);
+            cross(a, b, c) > 0.
+                && remaining.iter().map(|&j| point(j)).all(|p| {
+                    p == a
+                        || p == b
+                        || p == c
+                        || cross(a, b, p) < 0.
+                        || cross(b, c, p) < 0.
+                        || cross(c, a, p) < 0.
+                })
+        });
+
+        let Some(i) = ear else {
+            break;
+        };
+        triangles.push([
+            remaining[(i + n - 1) % n],
+            remaining[i],
+            remaining[(i + 1) % n],
+        ]);
+        remaining.remove(i);
+    }
+
+    triangles
+        .extend((1..remaining.len() - 1).map(|i| [remaining[0], remaining[i], remaining[i + 1]]));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Color, import::TestDir, material::Material, ray::Ray};
+
+    const QUAD: &str =
+        "mtllib quad.mtl\nv 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nusemtl red\nf 1 2 3 4\n";
+    const MTL: &str = "newmtl red\nKd 1 0 0\n";
+
+    #[test]
+    fn resolves_materials_next_to_the_obj() {
+        let directory = TestDir::new("obj_mtl");
+        let obj = directory.write("quad.obj", QUAD);
+        directory.write("quad.mtl", MTL);
+        let mut store = ObjectStore::new();
+
+        let objects = load(&mut store, obj, &ObjOptions::default()).unwrap();
+
+        assert_eq!(objects.len(), 1);
+        let Some(crate::object::Object::Mesh(mesh)) = store.object(objects[0]) else {
+            panic!("expected a mesh");
+        };
+        assert_eq!(mesh.triangles().len(), 2);
+        assert_eq!(store.material_name(mesh.material()), "red");
+        let Material::Phong(material) = store.material(mesh.material()) else {
+            panic!("expected a Phong material");
+        };
+        assert_eq!(material.diffuse, Color::new(1., 0., 0.));
+    }
+
+    #[test]
+    fn falls_back_to_the_default_material() {
+        let directory = TestDir::new("obj_no_mtl");
+        let obj = directory.write("quad.obj", QUAD);
+        let mut store = ObjectStore::new();
+        let options = ObjOptions::default();
+
+        let objects = load(&mut store, obj, &options).unwrap();
+
+        let Some(crate::object::Object::Mesh(mesh)) = store.object(objects[0]) else {
+            panic!("expected a mesh");
+        };
+        assert_eq!(
+            *store.material(mesh.material()),
+            options.default_material.into()
+        );
+    }
+
+    #[test]
+    fn ear_clipping_splits_concave_polygons() {
+        // an arrowhead pointing up, with its notch at the second vertex
+        let directory = TestDir::new("obj_concave");
+        let obj = directory.write(
+            "arrow.obj",
+            "v 0 0 0\nv 1 1 0\nv 2 0 0\nv 1 3 0\nf 1 2 3 4\n",
+        );
+
+        let down_at = |x, y| Ray::new(vec3(x, y, 1.), Vec3::NEG_Z, 0.);
+        for (triangulation, fills_notch) in [
+            (Triangulation::Fan, true),
+            (Triangulation::EarClipping, false),
+        ] {
+            let mut store = ObjectStore::new();
+            let options = ObjOptions {
+                triangulation,
+                ..Default::default()
+            };
+            load(&mut store, &obj, &options).unwrap();
+
+            assert!(store.intersect(&down_at(1., 2.)).is_some());
+            assert!(store.intersect(&down_at(0.5, 0.6)).is_some());
+            assert_eq!(
+                store.intersect(&down_at(1., 0.5)).is_some(),
+                fills_notch,
+                "{triangulation:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn reports_missing_files() {
+        let mut store = ObjectStore::new();
+        let error = load(&mut store, "does/not/exist.obj", &ObjOptions::default()).unwrap_err();
+
+        assert!(format!("{error}").contains("does/not/exist.obj"));
+    }
+}
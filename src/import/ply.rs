@@ -0,0 +1,454 @@
+use std::{fs, path::Path, str::SplitAsciiWhitespace};
+
+use eyre::{bail, eyre, Result, WrapErr};
+use glam::{vec2, vec3, Vec2, Vec3};
+
+use crate::{
+    color::Color,
+    object_store::{ObjectId, ObjectStore},
+};
+
+use super::{validated_mesh, MeshOptions};
+
+/// Loads the mesh of the ASCII or binary PLY file at `path` into `store`. Polygons are
+/// fan-triangulated; vertex normals, texture coordinates and colors are kept when present.
+pub fn load(
+    store: &mut ObjectStore,
+    path: impl AsRef<Path>,
+    options: &MeshOptions,
+) -> Result<ObjectId> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).wrap_err_with(|| format!("opening {}", path.display()))?;
+    let ply = parse(&bytes).wrap_err_with(|| format!("parsing {}", path.display()))?;
+
+    let normal_matrix = options.transform.matrix3.inverse().transpose();
+    let positions = ply
+        .positions
+        .iter()
+        .map(|p| options.transform.transform_point3(*p))
+        .collect();
+    let normals = ply
+        .normals
+        .iter()
+        .map(|n| (normal_matrix * *n).normalize())
+        .collect();
+
+    let name = path.file_stem().unwrap_or_default().to_string_lossy();
+    let material = store.add_material("default", options.default_material);
+    let mut mesh = validated_mesh(&name, positions, normals, ply.uvs, ply.triangles, material)
+        .wrap_err_with(|| format!("loading {}", path.display()))?;
+    if !ply.colors.is_empty() {
+        mesh = mesh.with_colors(ply.colors);
+    }
+
+    Ok(store.add_object(mesh))
+}
+
+#[derive(Debug, Default)]
+struct Ply {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<Vec2>,
+    colors: Vec<Color>,
+    triangles: Vec<[u32; 3]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Result<Self> {
+        Ok(match name {
+            "char" | "int8" => Scalar::I8,
+            "uchar" | "uint8" => Scalar::U8,
+            "short" | "int16" => Scalar::I16,
+            "ushort" | "uint16" => Scalar::U16,
+            "int" | "int32" => Scalar::I32,
+            "uint" | "uint32" => Scalar::U32,
+            "float" | "float32" => Scalar::F32,
+            "double" | "float64" => Scalar::F64,
+            _ => bail!("unknown property type {name:?}"),
+        })
+    }
+
+    /// Maps a color channel to the unit range; integer channels span their type's range.
+    fn normalize(self, value: f64) -> f32 {
+        let max = match self {
+            Scalar::U8 => u8::MAX as f64,
+            Scalar::U16 => u16::MAX as f64,
+            Scalar::U32 => u32::MAX as f64,
+            _ => 1.,
+        };
+        (value / max) as f32
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Property {
+    Scalar(String, Scalar),
+    /// List of `item`s preceded by its length, stored as a `count`.
+    List {
+        name: String,
+        count: Scalar,
+        item: Scalar,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl Element {
+    fn scalar(&self, names: &[&str]) -> Option<(usize, Scalar)> {
+        self.properties
+            .iter()
+            .enumerate()
+            .find_map(|(i, property)| match property {
+                Property::Scalar(name, scalar) if names.contains(&name.as_str()) => {
+                    Some((i, *scalar))
+                }
+                _ => None,
+            })
+    }
+}
+
+fn parse(bytes: &[u8]) -> Result<Ply> {
+    let (format, elements, body) = parse_header(bytes)?;
+
+    let mut body = match format {
+        Format::Ascii => Body::Ascii(
+            std::str::from_utf8(body)
+                .wrap_err("ASCII data is not valid UTF-8")?
+                .split_ascii_whitespace(),
+        ),
+        Format::BinaryLittleEndian | Format::BinaryBigEndian => Body::Binary {
+            bytes: body,
+            big_endian: format == Format::BinaryBigEndian,
+        },
+    };
+
+    let mut ply = Ply::default();
+    for element in &elements {
+        match element.name.as_str() {
+            "vertex" => read_vertices(element, &mut body, &mut ply)?,
+            "face" => read_faces(element, &mut body, &mut ply)?,
+            _ => {
+                for _ in 0..element.count {
+                    read_values(element, &mut body)?;
+                }
+            }
+        }
+    }
+
+    Ok(ply)
+}
+
+/// Returns the format, the elements declared in the header and the data following it.
+fn parse_header(bytes: &[u8]) -> Result<(Format, Vec<Element>, &[u8])> {
+    let mut rest = bytes;
+    let mut next_line = || -> Result<String> {
+        let end = rest
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or_else(|| eyre!("header has no end_header line"))?;
+        let line = String::from_utf8_lossy(&rest[..end]).trim().to_owned();
+        rest = &rest[end + 1..];
+        Ok(line)
+    };
+
+    if next_line()? != "ply" {
+        bail!("missing ply magic number");
+    }
+
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    loop {
+        let line = next_line()?;
+        let words = line.split_ascii_whitespace().collect::<Vec<_>>();
+
+        match words.as_slice() {
+            ["end_header"] => break,
+            ["format", name, _version] => {
+                format = Some(match *name {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::BinaryLittleEndian,
+                    "binary_big_endian" => Format::BinaryBigEndian,
+                    _ => bail!("unknown format {name:?}"),
+                });
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .wrap_err_with(|| format!("invalid count of element {name:?}"))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count, item, name] => {
+                let property = Property::List {
+                    name: name.to_string(),
+                    count: Scalar::parse(count)?,
+                    item: Scalar::parse(item)?,
+                };
+                add_property(&mut elements, property)?;
+            }
+            ["property", scalar, name] => {
+                let property = Property::Scalar(name.to_string(), Scalar::parse(scalar)?);
+                add_property(&mut elements, property)?;
+            }
+            ["comment" | "obj_info", ..] | [] => {}
+            _ => bail!("invalid header line {line:?}"),
+        }
+    }
+
+    let format = format.ok_or_else(|| eyre!("header has no format line"))?;
+    Ok((format, elements, rest))
+}
+
+fn add_property(elements: &mut [Element], property: Property) -> Result<()> {
+    match elements.last_mut() {
+        Some(element) => element.properties.push(property),
+        None => bail!("property declared before any element"),
+    }
+    Ok(())
+}
+
+fn read_vertices(element: &Element, body: &mut Body, ply: &mut Ply) -> Result<()> {
+    let position = ["x", "y", "z"].map(|name| element.scalar(&[name]));
+    let normal = ["nx", "ny", "nz"].map(|name| element.scalar(&[name]));
+    let uv = [
+        element.scalar(&["u", "s", "texture_u"]),
+        element.scalar(&["v", "t", "texture_v"]),
+    ];
+    let color = [
+        element.scalar(&["red", "diffuse_red"]),
+        element.scalar(&["green", "diffuse_green"]),
+        element.scalar(&["blue", "diffuse_blue"]),
+    ];
+
+    let [Some(x), Some(y), Some(z)] = position else {
+        bail!("vertices have no x, y and z properties");
+    };
+
+    for _ in 0..element.count {
+        let values = read_values(element, body)?;
+        let value = |(i, _): (usize, Scalar)| values[i] as f32;
+
+        ply.positions.push(vec3(value(x), value(y), value(z)));
+        if let [Some(nx), Some(ny), Some(nz)] = normal {
+            ply.normals.push(vec3(value(nx), value(ny), value(nz)));
+        }
+        if let [Some(u), Some(v)] = uv {
+            ply.uvs.push(vec2(value(u), value(v)));
+        }
+        if let [Some(r), Some(g), Some(b)] = color {
+            let channel = |(i, scalar): (usize, Scalar)| scalar.normalize(values[i]);
+            ply.colors
+                .push(Color::new(channel(r), channel(g), channel(b)));
+        }
+    }
+
+    Ok(())
+}
+
+fn read_faces(element: &Element, body: &mut Body, ply: &mut Ply) -> Result<()> {
+    let indices = element
+        .properties
+        .iter()
+        .position(|property| {
+            matches!(property, Property::List { name, .. }
+                if name == "vertex_indices" || name == "vertex_index")
+        })
+        .ok_or_else(|| eyre!("faces have no vertex_indices property"))?;
+
+    for f in 0..element.count {
+        let mut face = Vec::new();
+        for (i, property) in element.properties.iter().enumerate() {
+            match property {
+                Property::Scalar(_, scalar) => {
+                    body.read(*scalar)?;
+                }
+                Property::List { count, item, .. } => {
+                    let count = body.read(*count)? as usize;
+                    for _ in 0..count {
+                        let value = body.read(*item)?;
+                        if i == indices {
+                            if !(0. ..=u32::MAX as f64).contains(&value) || value.fract() != 0. {
+                                bail!("face {f} has invalid vertex index {value}");
+                            }
+                            face.push(value as u32);
+                        }
+                    }
+                }
+            }
+        }
+
+        for i in 1..face.len().saturating_sub(1) {
+            ply.triangles.push([face[0], face[i], face[i + 1]]);
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads one instance of `element`, returning the value of each scalar property; lists are
+/// skipped and yield zero.
+fn read_values(element: &Element, body: &mut Body) -> Result<Vec<f64>> {
+    element
+        .properties
+        .iter()
+        .map(|property| match property {
+            Property::Scalar(_, scalar) => body.read(*scalar),
+            Property::List { count, item, .. } => {
+                let count = body.read(*count)? as usize;
+                for _ in 0..count {
+                    body.read(*item)?;
+                }
+                Ok(0.)
+            }
+        })
+        .collect()
+}
+
+enum Body<'a> {
+    Ascii(SplitAsciiWhitespace<'a>),
+    Binary { bytes: &'a [u8], big_endian: bool },
+}
+
+impl Body<'_> {
+    fn read(&mut self, scalar: Scalar) -> Result<f64> {
+        match self {
+            Body::Ascii(tokens) => {
+                let token = tokens
+                    .next()
+                    .ok_or_else(|| eyre!("unexpected end of data"))?;
+                token
+                    .parse()
+                    .wrap_err_with(|| format!("invalid number {token:?}"))
+            }
+            Body::Binary { bytes, big_endian } => {
+                let big_endian = *big_endian;
+                Ok(match scalar {
+                    Scalar::I8 => i8::from_le_bytes(take(bytes, big_endian)?) as f64,
+                    Scalar::U8 => u8::from_le_bytes(take(bytes, big_endian)?) as f64,
+                    Scalar::I16 => i16::from_le_bytes(take(bytes, big_endian)?) as f64,
+                    Scalar::U16 => u16::from_le_bytes(take(bytes, big_endian)?) as f64,
+                    Scalar::I32 => i32::from_le_bytes(take(bytes, big_endian)?) as f64,
+                    Scalar::U32 => u32::from_le_bytes(take(bytes, big_endian)?) as f64,
+                    Scalar::F32 => f32::from_le_bytes(take(bytes, big_endian)?) as f64,
+                    Scalar::F64 => f64::from_le_bytes(take(bytes, big_endian)?),
+                })
+            }
+        }
+    }
+}
+
+/// Splits the next `N` bytes off `bytes`, in little-endian order.
+fn take<const N: usize>(bytes: &mut &[u8], big_endian: bool) -> Result<[u8; N]> {
+    if bytes.len() < N {
+        bail!("unexpected end of data");
+    }
+    let (value, rest) = bytes.split_at(N);
+    *bytes = rest;
+
+    let mut value: [u8; N] = value.try_into()?;
+    if big_endian {
+        value.reverse();
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASCII: &str = "ply
+format ascii 1.0
+comment a red and blue quad
+element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0
+1 0 0 255 0 0
+1 1 0 0 0 255
+0 1 0 0 0 255
+4 0 1 2 3
+";
+
+    #[test]
+    fn parses_ascii_with_colors() {
+        let ply = parse(ASCII.as_bytes()).unwrap();
+
+        assert_eq!(ply.positions.len(), 4);
+        assert_eq!(ply.triangles, [[0, 1, 2], [0, 2, 3]]);
+        assert_eq!(ply.colors[0], Color::new(1., 0., 0.));
+        assert_eq!(ply.colors[3], Color::new(0., 0., 1.));
+        assert!(ply.normals.is_empty() && ply.uvs.is_empty());
+    }
+
+    #[test]
+    fn parses_binary_like_ascii() {
+        let header = ASCII
+            .split("end_header\n")
+            .next()
+            .unwrap()
+            .replace("ascii", "binary_big_endian");
+        let mut bytes = format!("{header}end_header\n").into_bytes();
+        for (position, color) in [
+            ([0f32, 0., 0.], [255u8, 0, 0]),
+            ([1., 0., 0.], [255, 0, 0]),
+            ([1., 1., 0.], [0, 0, 255]),
+            ([0., 1., 0.], [0, 0, 255]),
+        ] {
+            bytes.extend(position.iter().flat_map(|p| p.to_be_bytes()));
+            bytes.extend(color);
+        }
+        bytes.push(4);
+        bytes.extend([0i32, 1, 2, 3].iter().flat_map(|i| i.to_be_bytes()));
+
+        let binary = parse(&bytes).unwrap();
+        let ascii = parse(ASCII.as_bytes()).unwrap();
+
+        assert_eq!(binary.positions, ascii.positions);
+        assert_eq!(binary.colors, ascii.colors);
+        assert_eq!(binary.triangles, ascii.triangles);
+    }
+
+    #[test]
+    fn rejects_negative_indices() {
+        let negative = ASCII.replace("4 0 1 2 3", "4 0 1 -2 3");
+        let error = parse(negative.as_bytes()).unwrap_err();
+        assert!(format!("{error:#}").contains("-2"), "{error:#}");
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let truncated = &ASCII[..ASCII.len() - 4];
+        assert!(parse(truncated.as_bytes()).is_err());
+    }
+}
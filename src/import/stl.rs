@@ -0,0 +1,144 @@
+use std::{fs, path::Path};
+
+use eyre::{bail, eyre, Result, WrapErr};
+use glam::{vec3, Vec3};
+
+use crate::object_store::{ObjectId, ObjectStore};
+
+use super::{validated_mesh, MeshOptions};
+
+/// Size of a binary STL header and triangle count.
+const HEADER_SIZE: usize = 84;
+/// Size of a binary STL facet: normal, three vertices and an attribute byte count.
+const FACET_SIZE: usize = 50;
+
+/// Loads the mesh of the ASCII or binary STL file at `path` into `store`. STL stores every facet
+/// on its own, so the mesh is flat shaded; the stored facet normals are ignored.
+pub fn load(
+    store: &mut ObjectStore,
+    path: impl AsRef<Path>,
+    options: &MeshOptions,
+) -> Result<ObjectId> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).wrap_err_with(|| format!("opening {}", path.display()))?;
+    let positions = parse(&bytes).wrap_err_with(|| format!("parsing {}", path.display()))?;
+
+    let positions = positions
+        .into_iter()
+        .map(|p| options.transform.transform_point3(p))
+        .collect::<Vec<_>>();
+    let triangles = (0..positions.len() as u32 / 3)
+        .map(|i| [3 * i, 3 * i + 1, 3 * i + 2])
+        .collect();
+
+    let name = path.file_stem().unwrap_or_default().to_string_lossy();
+    let material = store.add_material("default", options.default_material);
+    let mesh = validated_mesh(
+        &name,
+        positions,
+        Vec::new(),
+        Vec::new(),
+        triangles,
+        material,
+    )
+    .wrap_err_with(|| format!("loading {}", path.display()))?;
+
+    Ok(store.add_object(mesh))
+}
+
+/// Returns the vertices of all facets, three per facet.
+fn parse(bytes: &[u8]) -> Result<Vec<Vec3>> {
+    // ASCII files start with "solid", but so do the headers of some binary files; the size of a
+    // binary file is fixed by its triangle count
+    if bytes.len() >= HEADER_SIZE {
+        let count = u32::from_le_bytes(bytes[80..84].try_into()?) as usize;
+        if bytes.len() == HEADER_SIZE + count * FACET_SIZE {
+            return Ok(parse_binary(&bytes[HEADER_SIZE..]));
+        }
+    }
+
+    if !bytes.starts_with(b"solid") {
+        bail!("neither a binary nor an ASCII STL file");
+    }
+    parse_ascii(std::str::from_utf8(bytes).wrap_err("ASCII STL is not valid UTF-8")?)
+}
+
+fn parse_binary(facets: &[u8]) -> Vec<Vec3> {
+    let float = |bytes: &[u8]| f32::from_le_bytes(bytes.try_into().unwrap());
+
+    facets
+        .chunks_exact(FACET_SIZE)
+        .flat_map(|facet| {
+            // skip the normal
+            facet[12..48]
+                .chunks_exact(12)
+                .map(move |v| vec3(float(&v[0..4]), float(&v[4..8]), float(&v[8..12])))
+        })
+        .collect()
+}
+
+fn parse_ascii(text: &str) -> Result<Vec<Vec3>> {
+    let mut tokens = text.split_ascii_whitespace();
+    let mut positions = Vec::new();
+
+    while let Some(token) = tokens.next() {
+        if token != "vertex" {
+            continue;
+        }
+
+        let mut coordinate = || -> Result<f32> {
+            let token = tokens
+                .next()
+                .ok_or_else(|| eyre!("unexpected end of file"))?;
+            token
+                .parse()
+                .wrap_err_with(|| format!("invalid coordinate {token:?}"))
+        };
+        positions.push(vec3(coordinate()?, coordinate()?, coordinate()?));
+    }
+
+    if positions.len() % 3 != 0 {
+        bail!("facet with {} vertices", positions.len() % 3);
+    }
+    Ok(positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASCII: &str = "solid triangle
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 0 1 0
+    endloop
+  endfacet
+endsolid triangle
+";
+
+    #[test]
+    fn parses_ascii_and_binary() {
+        let ascii = parse(ASCII.as_bytes()).unwrap();
+        assert_eq!(ascii, [Vec3::ZERO, Vec3::X, Vec3::Y]);
+
+        // a header starting with "solid" must not be mistaken for an ASCII file
+        let mut binary = b"solid".to_vec();
+        binary.resize(80, 0);
+        binary.extend(1u32.to_le_bytes());
+        for v in [Vec3::Z, Vec3::ZERO, Vec3::X, Vec3::Y] {
+            binary.extend(v.to_array().iter().flat_map(|c| c.to_le_bytes()));
+        }
+        binary.extend(0u16.to_le_bytes());
+
+        assert_eq!(parse(&binary).unwrap(), ascii);
+    }
+
+    #[test]
+    fn rejects_incomplete_facets() {
+        let incomplete = ASCII.replace("      vertex 0 1 0\n", "");
+        assert!(parse(incomplete.as_bytes()).is_err());
+        assert!(parse(b"not an stl file").is_err());
+    }
+}
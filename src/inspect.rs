@@ -0,0 +1,122 @@
+use std::fmt;
+
+use glam::Vec3;
+
+use crate::{
+    color::Color,
+    object_store::{MaterialId, ObjectId},
+    ray::Ray,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RayKind {
+    Primary,
+    Reflection,
+    Transmission,
+}
+
+/// Record of a single traced ray: what it hit, how each light contributed to the shading and the
+/// secondary rays spawned from the hit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RayTrace {
+    pub kind: RayKind,
+    pub ray: Ray,
+    pub hit: Option<TraceHit>,
+    pub lights: Vec<LightContribution>,
+    pub children: Vec<RayTrace>,
+    /// Color returned along the ray, including the contributions of its children.
+    pub color: Color,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceHit {
+    pub object: ObjectId,
+    pub material: MaterialId,
+    pub material_name: String,
+    pub position: Vec3,
+    pub normal: Vec3,
+    pub distance: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightContribution {
+    /// Index of the light in the [`ObjectStore`](crate::object_store::ObjectStore).
+    pub light: usize,
+    /// Brightness reaching the hit, zero if the light is shadowed.
+    pub brightness: f32,
+    pub diffuse: Color,
+    pub specular: Color,
+}
+
+impl RayTrace {
+    pub fn new(kind: RayKind, ray: Ray) -> Self {
+        RayTrace {
+            kind,
+            ray,
+            hit: None,
+            lights: Vec::new(),
+            children: Vec::new(),
+            color: Color::default(),
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+        writeln!(
+            f,
+            "{indent}{:?} ray from {} towards {}: color {}",
+            self.kind,
+            vector(self.ray.origin),
+            vector(self.ray.direction),
+            color(self.color),
+        )?;
+
+        let Some(hit) = &self.hit else {
+            return writeln!(f, "{indent}  missed, background");
+        };
+
+        writeln!(
+            f,
+            "{indent}  hit object {} with material {} \"{}\"",
+            hit.object.0, hit.material.0, hit.material_name,
+        )?;
+        writeln!(
+            f,
+            "{indent}  position {}, normal {}, depth {:.4}",
+            vector(hit.position),
+            vector(hit.normal),
+            hit.distance,
+        )?;
+
+        for light in &self.lights {
+            writeln!(
+                f,
+                "{indent}  light {}: brightness {:.3}, diffuse {}, specular {}",
+                light.light,
+                light.brightness,
+                color(light.diffuse),
+                color(light.specular),
+            )?;
+        }
+
+        for child in &self.children {
+            child.write(f, depth + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for RayTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+fn vector(v: Vec3) -> String {
+    format!("({:.3}, {:.3}, {:.3})", v.x, v.y, v.z)
+}
+
+fn color(c: Color) -> String {
+    format!("({:.3}, {:.3}, {:.3})", c.r(), c.g(), c.b())
+}
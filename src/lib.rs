@@ -0,0 +1,24 @@
+//! Whitted-style ray tracer. Scenes are assembled in the [`object_store::ObjectStore`], rendered
+//! through [`raytracer::Raytracer`] and written out with [`output`].
+
+pub mod aabb;
+pub mod animation;
+mod bvh;
+pub mod camera;
+pub mod color;
+mod consts;
+pub mod import;
+pub mod inspect;
+pub mod light;
+pub mod material;
+pub mod medium;
+pub mod object;
+pub mod object_store;
+pub mod output;
+pub mod ray;
+pub mod raytracer;
+pub mod scene;
+mod solver;
+pub mod stats;
+pub mod subdivision;
+pub mod surface;
@@ -1,14 +1,8 @@
 use glam::Vec3;
 
-use crate::{
-    object::{Intersectable, Object},
-    object_store::OBJECT_STORE,
-};
-
 pub struct Light {
     pub position: Vec3,
     pub brightness: f32,
-    distance_to_light: f32,
 }
 
 impl Light {
@@ -16,27 +10,92 @@ impl Light {
         Light {
             position,
             brightness,
-            distance_to_light: 0.,
         }
     }
 
-    pub fn lightray(&mut self, pos: Vec3) -> Vec3 {
-        self.distance_to_light = (self.position - pos).length();
+    /// Returns the direction from `pos` towards the light.
+    pub fn lightray(&self, pos: Vec3) -> Vec3 {
         (self.position - pos).normalize()
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        object::{csg::Csg, group::Group, sphere::Sphere, torus::Torus, Object},
+        object_store::{MaterialId, ObjectStore},
+        ray::Ray,
+    };
+
+    /// Traces a ray onto `objects`, then casts a shadow ray from the hit towards `light`.
+    fn shadow(objects: &[Object], light: &Light, ray: Ray) -> f32 {
+        let mut store = ObjectStore::new();
+        for object in objects {
+            store.add_object(object.clone());
+        }
+
+        let (_, hit) = store.intersect(&ray).unwrap();
+        let pos = ray.at(hit.distance);
+        let light_ray = light.lightray(pos);
+
+        store.light_brightness(light, &Ray::spawn(pos, hit.normal, light_ray, 0.))
+    }
 
-    pub fn brightness(&self, source: &Object, pos: Vec3, ray: Vec3) -> f32 {
-        for object in unsafe { OBJECT_STORE.objects() } {
-            if object != source {
-                let Some(s) = object.intersect(pos, ray) else {
-                    continue;
-                };
-                if s <= self.distance_to_light {
-                    return 0.;
-                }
-            }
+    #[test]
+    fn no_shadow_acne() {
+        let objects = [Sphere::new(Vec3::ZERO, 1., MaterialId(0)).into()];
+        let light = Light::new(Vec3::new(3., 4., 5.), 1.);
+
+        for direction in [Vec3::new(1., 1., 1.), Vec3::new(0.3, 0.8, 0.2), Vec3::Z] {
+            let direction = direction.normalize();
+            let ray = Ray::new(direction * 5., -direction, 0.);
+            assert_eq!(shadow(&objects, &light, ray), 1.);
         }
+    }
+
+    #[test]
+    fn torus_shadows_itself() {
+        // the far side of the ring blocks the light from reaching the inside of the near side
+        let objects = [Torus::new(Vec3::ZERO, Vec3::Y, 1., 0.25, MaterialId(0)).into()];
+        let light = Light::new(Vec3::new(3., 0., 0.), 1.);
+        let ray = Ray::new(Vec3::new(-0.85, 2., 0.), Vec3::NEG_Y, 0.);
+
+        assert_eq!(shadow(&objects, &light, ray), 0.);
+    }
+
+    #[test]
+    fn bowl_shadows_itself() {
+        // a hemispherical bowl opening upwards; its rim shadows the inside when lit from low on
+        // one side, but not when lit from above
+        let bowl = Csg::difference(
+            Sphere::new(Vec3::ZERO, 1., MaterialId(0)).into(),
+            Sphere::new(Vec3::Y, 1., MaterialId(0)).into(),
+        );
+        let objects = [bowl.into()];
+        let ray = Ray::new(Vec3::new(-0.5, 3., 0.), Vec3::NEG_Y, 0.);
+
+        let low = Light::new(Vec3::new(5., 0.2, 0.), 1.);
+        assert_eq!(shadow(&objects, &low, ray), 0.);
+
+        let high = Light::new(Vec3::new(0., 5., 0.), 1.);
+        assert_eq!(shadow(&objects, &high, ray), 1.);
+    }
+
+    #[test]
+    fn group_members_shadow_each_other() {
+        // a shadow ray leaving one member of a group is still blocked by the others
+        let group = Group::new(vec![
+            Sphere::new(Vec3::ZERO, 1., MaterialId(0)).into(),
+            Sphere::new(Vec3::new(3., 0., 0.), 1., MaterialId(0)).into(),
+        ]);
+        let objects = [group.into()];
+        let ray = Ray::new(Vec3::new(0.8, 5., 0.), Vec3::NEG_Y, 0.);
+
+        let beside = Light::new(Vec3::new(6., 0.6, 0.), 1.);
+        assert_eq!(shadow(&objects, &beside, ray), 0.);
 
-        self.brightness
+        let above = Light::new(Vec3::new(0.8, 5., 0.), 1.);
+        assert_eq!(shadow(&objects, &above, ray), 1.);
     }
 }
@@ -1,25 +1,17 @@
-mod camera;
-mod color;
-mod consts;
-mod light;
-mod object;
-mod object_store;
-mod raytracer;
-mod surface;
-
-use std::rc::Rc;
+use std::{env, rc::Rc};
 
 use eyre::Result;
-
-use raytracer::Raytracer;
+use rust_tracer::raytracer::Raytracer;
 use winit::{
-    dpi::LogicalSize,
-    event::{Event, WindowEvent},
+    dpi::{LogicalSize, PhysicalPosition},
+    event::{ElementState, Event, MouseButton, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
     window::WindowBuilder,
 };
 
 fn main() -> Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
     let event_loop = EventLoop::new()?;
     let window = Rc::new(
         WindowBuilder::new()
@@ -30,7 +22,13 @@ fn main() -> Result<()> {
     let context = softbuffer::Context::new(window.clone()).unwrap();
     let mut surface = softbuffer::Surface::new(&context, window.clone()).unwrap();
 
-    let raytracer = Raytracer::new(window.inner_size().width, window.inner_size().height);
+    // the Cornell box scene to view, in the working directory unless given
+    let scene = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "CornellBox-Original.obj".to_owned());
+    let size = window.inner_size();
+    let mut raytracer = Raytracer::new(scene, size.width, size.height)?;
+    let mut cursor = PhysicalPosition::new(0., 0.);
 
     event_loop.run(move |event, elwt| {
         elwt.set_control_flow(ControlFlow::Wait);
@@ -61,6 +59,26 @@ fn main() -> Result<()> {
 
                 buffer.present().unwrap();
             }
+            Event::WindowEvent {
+                window_id,
+                event: WindowEvent::CursorMoved { position, .. },
+            } if window_id == window.id() => cursor = position,
+            // clicking a pixel prints how its color came about
+            Event::WindowEvent {
+                window_id,
+                event:
+                    WindowEvent::MouseInput {
+                        state: ElementState::Pressed,
+                        button: MouseButton::Left,
+                        ..
+                    },
+            } if window_id == window.id() => {
+                let (x, y) = (cursor.x as u32, cursor.y as u32);
+                match raytracer.inspect(x, y) {
+                    Some(trace) => println!("pixel ({x}, {y})\n{trace}"),
+                    None => println!("pixel ({x}, {y}) lies outside of the projection"),
+                }
+            }
             Event::WindowEvent {
                 window_id,
                 event: WindowEvent::CloseRequested,
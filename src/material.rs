@@ -0,0 +1,339 @@
+pub mod conductor;
+pub mod dielectric;
+pub mod emissive;
+pub mod lambertian;
+pub(crate) mod microfacet;
+pub mod mirror;
+pub mod pbr;
+pub mod plastic;
+
+use std::f32::consts::PI;
+
+use enum_dispatch::enum_dispatch;
+use glam::{Vec2, Vec3};
+
+use crate::{color::Color, surface::SurfaceMaterial};
+
+/// Reflection model of a surface, stored in the [`ObjectStore`](crate::object_store::ObjectStore)'s
+/// material table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[enum_dispatch]
+pub enum Material {
+    /// Classic Phong shading with mirror reflection and straight transmission.
+    Phong(SurfaceMaterial),
+    Pbr(pbr::PbrMaterial),
+    Lambertian(lambertian::Lambertian),
+    Mirror(mirror::Mirror),
+    Dielectric(dielectric::Dielectric),
+    RoughConductor(conductor::RoughConductor),
+    Plastic(plastic::Plastic),
+    Emissive(emissive::Emissive),
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material::Phong(SurfaceMaterial::default())
+    }
+}
+
+impl Material {
+    /// Returns the material with its diffuse color replaced by an interpolated vertex color.
+    /// Materials without a diffuse color are returned unchanged.
+    pub fn with_vertex_color(self, color: Color) -> Self {
+        match self {
+            Material::Phong(surface) => SurfaceMaterial {
+                diffuse: color,
+                ..surface
+            }
+            .into(),
+            Material::Pbr(pbr) => pbr::PbrMaterial {
+                base_color: color,
+                ..pbr
+            }
+            .into(),
+            Material::Lambertian(_) => lambertian::Lambertian { albedo: color }.into(),
+            Material::Plastic(plastic) => plastic::Plastic {
+                diffuse: color,
+                ..plastic
+            }
+            .into(),
+            material => material,
+        }
+    }
+}
+
+/// Scattering of light at a surface.
+///
+/// `normal` is the surface normal as reported by the hit, which points out of solids; opaque
+/// surfaces are two-sided and reflect on whichever side they are seen from. All directions point
+/// away from the surface: `view` towards the viewer and `light` towards where light comes from.
+#[enum_dispatch(Material)]
+pub trait Bsdf {
+    /// Returns the BSDF times the cosine between the normal and `light`: the share of the light
+    /// arriving from `light` that leaves towards `view`. Perfectly specular scattering can't be
+    /// reached by a given direction and is left out.
+    fn eval(&self, normal: Vec3, view: Vec3, light: Vec3) -> Color;
+
+    /// Importance samples a direction light arrives from, using the uniform random numbers in
+    /// `u`. Returns `None` if the sampled direction carries no light.
+    fn sample(&self, normal: Vec3, view: Vec3, u: Vec3) -> Option<BsdfSample>;
+
+    /// Density of [`Bsdf::sample`] choosing `light`, per solid angle; zero for perfectly specular
+    /// directions.
+    fn pdf(&self, normal: Vec3, view: Vec3, light: Vec3) -> f32;
+
+    /// Returns the glossy and specular directions a Whitted-style tracer follows with secondary
+    /// rays, sampled with `u` where they are not unique. Diffuse scattering is lit by the lights
+    /// directly and not followed.
+    fn scatter(&self, _normal: Vec3, _view: Vec3, _u: Vec2) -> Scatter {
+        Scatter::default()
+    }
+
+    /// Whether the directions of [`Bsdf::scatter`] spread over a glossy lobe, to be averaged over
+    /// several samples, rather than being unique.
+    fn glossy(&self) -> bool {
+        false
+    }
+
+    /// Light emitted by the surface towards `view`.
+    fn emission(&self) -> Color {
+        Color::default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lobe {
+    Diffuse,
+    Glossy,
+    /// A single direction, such as a mirror reflection.
+    Specular,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BsdfSample {
+    pub direction: Vec3,
+    /// Share of the light arriving from `direction` that leaves towards the viewer, divided by
+    /// the density of choosing it.
+    pub weight: Color,
+    pub lobe: Lobe,
+}
+
+/// Secondary rays spawned at a hit, on the side of the viewer and through the surface.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Scatter {
+    pub reflection: Option<BsdfSample>,
+    pub transmission: Option<BsdfSample>,
+}
+
+/// Flips `normal` to the side of the surface `view` lies on.
+pub(crate) fn facing(normal: Vec3, view: Vec3) -> Vec3 {
+    if normal.dot(view) < 0. {
+        -normal
+    } else {
+        normal
+    }
+}
+
+/// Rotates `local` from the frame where the normal is +Z into world space.
+pub(crate) fn to_world(normal: Vec3, local: Vec3) -> Vec3 {
+    let (tangent, bitangent) = normal.any_orthonormal_pair();
+    tangent * local.x + bitangent * local.y + normal * local.z
+}
+
+/// Mirrors `view` about `normal`.
+pub(crate) fn reflect(view: Vec3, normal: Vec3) -> Vec3 {
+    2. * view.dot(normal) * normal - view
+}
+
+/// Refracts `view` through a surface with the given `normal` on its side, where `eta` is the
+/// ratio of the refractive index on the side of `view` to the one beyond. Returns `None` on total
+/// internal reflection.
+fn refract(view: Vec3, normal: Vec3, eta: f32) -> Option<Vec3> {
+    let cos_i = view.dot(normal);
+    let sin2_t = eta * eta * (1. - cos_i * cos_i).max(0.);
+    if sin2_t >= 1. {
+        return None;
+    }
+    let cos_t = (1. - sin2_t).sqrt();
+    Some((-view * eta + normal * (eta * cos_i - cos_t)).normalize())
+}
+
+/// Unpolarized Fresnel reflectance of a dielectric interface for the cosine `cos_i` of the
+/// incident angle and the index ratio `eta` as for [`refract`].
+fn fresnel_dielectric(cos_i: f32, eta: f32) -> f32 {
+    let sin2_t = eta * eta * (1. - cos_i * cos_i).max(0.);
+    if sin2_t >= 1. {
+        return 1.;
+    }
+    let cos_t = (1. - sin2_t).sqrt();
+
+    let parallel = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+    let perpendicular = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+    (parallel * parallel + perpendicular * perpendicular) / 2.
+}
+
+/// Cosine-weighted hemisphere direction in the frame where the normal is +Z.
+pub(crate) fn cosine_direction(u1: f32, u2: f32) -> Vec3 {
+    let r = u1.sqrt();
+    let phi = 2. * PI * u2;
+    Vec3::new(r * phi.cos(), r * phi.sin(), (1. - u1).max(0.).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+    use super::*;
+
+    const SAMPLES: usize = 50_000;
+
+    fn white() -> Color {
+        Color::new(1., 1., 1.)
+    }
+
+    /// Materials with a diffuse or glossy lobe, at their most reflective.
+    fn materials() -> Vec<Material> {
+        vec![
+            lambertian::Lambertian { albedo: white() }.into(),
+            conductor::RoughConductor {
+                color: white(),
+                roughness: 0.3,
+            }
+            .into(),
+            plastic::Plastic {
+                diffuse: white(),
+                ior: 1.5,
+            }
+            .into(),
+            pbr::PbrMaterial {
+                base_color: white(),
+                ..Default::default()
+            }
+            .into(),
+            SurfaceMaterial {
+                diffuse: Color::new(0.5, 0.5, 0.5),
+                ..Default::default()
+            }
+            .into(),
+        ]
+    }
+
+    #[test]
+    fn samples_agree_with_eval_and_pdf() {
+        let mut rng = SmallRng::seed_from_u64(3);
+        let view = Vec3::new(0.6, 0., 0.8);
+
+        for material in materials() {
+            // opaque surfaces are two-sided and reflect the same when seen from below
+            for view in [view, view * Vec3::new(1., 1., -1.)] {
+                let normal = Vec3::Z;
+                let mut energy = 0.;
+
+                for _ in 0..SAMPLES {
+                    let u = Vec3::new(rng.gen(), rng.gen(), rng.gen());
+                    let Some(sample) = material.sample(normal, view, u) else {
+                        continue;
+                    };
+                    let weight: Vec3 = sample.weight.into();
+                    energy += weight.x;
+                    if sample.lobe == Lobe::Specular {
+                        continue;
+                    }
+
+                    let pdf = material.pdf(normal, view, sample.direction);
+                    let eval: Vec3 = material.eval(normal, view, sample.direction).into();
+                    assert!(
+                        (weight - eval / pdf).length() < 1e-3 * (1. + weight.length()),
+                        "{material:?} weights {weight} but evaluates {}",
+                        eval / pdf
+                    );
+                }
+
+                let energy = energy / SAMPLES as f32;
+                assert!(energy <= 1.01, "{material:?} reflects {energy}");
+            }
+        }
+    }
+
+    #[test]
+    fn rough_scattering_spreads_around_the_smooth_directions() {
+        let mut rng = SmallRng::seed_from_u64(4);
+        let view = Vec3::new(0.6, 0., 0.8);
+        let mirrored = Vec3::new(-0.6, 0., 0.8);
+        let refracted = refract(view, Vec3::Z, 1. / 1.5).unwrap();
+
+        let surface = SurfaceMaterial {
+            reflection: 1.,
+            transparency: 1.,
+            ..Default::default()
+        };
+        let glass = dielectric::Dielectric::default();
+
+        for roughness in [0., 0.2] {
+            let cases: [(Material, Vec3, Vec3); 2] = [
+                (
+                    SurfaceMaterial {
+                        roughness,
+                        ..surface
+                    }
+                    .into(),
+                    mirrored,
+                    -view,
+                ),
+                (
+                    dielectric::Dielectric { roughness, ..glass }.into(),
+                    mirrored,
+                    refracted,
+                ),
+            ];
+
+            for (material, reflected, transmitted) in cases {
+                let scatters = (0..1000)
+                    .map(|_| material.scatter(Vec3::Z, view, Vec2::new(rng.gen(), rng.gen())))
+                    .collect::<Vec<_>>();
+                let reflections = scatters.iter().filter_map(|s| s.reflection).collect();
+                let transmissions = scatters.iter().filter_map(|s| s.transmission).collect();
+
+                for (expected, samples) in [(reflected, reflections), (transmitted, transmissions)]
+                {
+                    let samples: Vec<BsdfSample> = samples;
+                    assert!(samples.len() > 900, "{material:?} lost its rays");
+                    // the constant weight of the Phong lobe is only right if it rejects nothing
+                    if let Material::Phong(_) = material {
+                        assert_eq!(samples.len(), scatters.len());
+                    }
+
+                    let spread = samples
+                        .iter()
+                        .map(|sample| 1. - sample.direction.dot(expected))
+                        .sum::<f32>()
+                        / samples.len() as f32;
+                    if roughness == 0. {
+                        assert!(spread < 1e-5, "{material:?} spreads {spread}");
+                    } else {
+                        assert!(
+                            spread > 1e-4 && spread < 0.1,
+                            "{material:?} spreads {spread}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn snell_and_fresnel() {
+        let view = Vec3::new(0.6, 0., 0.8);
+        let refracted = refract(view, Vec3::Z, 1. / 1.5).unwrap();
+        let sin_t = refracted.truncate().length();
+        assert!((sin_t - 0.6 / 1.5).abs() < 1e-5);
+        assert!(refracted.z < 0.);
+
+        assert!((fresnel_dielectric(1., 1. / 1.5) - 0.04).abs() < 1e-4);
+        // beyond the critical angle inside glass, all light is reflected
+        let grazing = Vec3::new(0.8, 0., 0.6);
+        assert!(refract(grazing, Vec3::Z, 1.5).is_none());
+        assert_eq!(fresnel_dielectric(grazing.z, 1.5), 1.);
+        assert!(refract(view, Vec3::Z, 1.5).is_some());
+    }
+}
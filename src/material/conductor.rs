@@ -0,0 +1,66 @@
+use glam::{Vec2, Vec3};
+
+use crate::color::Color;
+
+use super::{facing, microfacet::Ggx, Bsdf, BsdfSample, Lobe, Scatter};
+
+/// Rough metal, reflecting through GGX microfacets tinted with the metal's color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoughConductor {
+    /// Reflectance at normal incidence; grazing reflections turn white.
+    pub color: Color,
+    /// Perceptual roughness from mirror-like 0 to fully rough 1.
+    pub roughness: f32,
+}
+
+impl Default for RoughConductor {
+    fn default() -> Self {
+        RoughConductor {
+            color: Color::new(0.9, 0.9, 0.9),
+            roughness: 0.3,
+        }
+    }
+}
+
+impl RoughConductor {
+    fn lobe(&self) -> Ggx {
+        Ggx::new(self.roughness, self.color.into())
+    }
+}
+
+impl Bsdf for RoughConductor {
+    fn eval(&self, normal: Vec3, view: Vec3, light: Vec3) -> Color {
+        self.lobe().eval(facing(normal, view), view, light).into()
+    }
+
+    fn sample(&self, normal: Vec3, view: Vec3, u: Vec3) -> Option<BsdfSample> {
+        let normal = facing(normal, view);
+        let lobe = self.lobe();
+        let light = lobe.sample(normal, view, Vec2::new(u.y, u.z))?;
+
+        Some(BsdfSample {
+            direction: light,
+            weight: lobe.weight(normal, view, light).into(),
+            lobe: Lobe::Glossy,
+        })
+    }
+
+    fn pdf(&self, normal: Vec3, view: Vec3, light: Vec3) -> f32 {
+        let normal = facing(normal, view);
+        if normal.dot(light) <= 0. {
+            return 0.;
+        }
+        self.lobe().pdf(normal, view, light)
+    }
+
+    fn scatter(&self, normal: Vec3, view: Vec3, u: Vec2) -> Scatter {
+        Scatter {
+            reflection: self.sample(normal, view, Vec3::new(0., u.x, u.y)),
+            transmission: None,
+        }
+    }
+
+    fn glossy(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,120 @@
+use glam::{Vec2, Vec3};
+
+use crate::color::Color;
+
+use super::{
+    facing, fresnel_dielectric, microfacet::Ggx, reflect, refract, Bsdf, BsdfSample, Lobe, Scatter,
+};
+
+/// Boundary of a transparent solid such as glass or water, reflecting and refracting according
+/// to Fresnel's equations. The solid is assumed to be surrounded by air; rays leaving it are
+/// recognized by the normal pointing away from the viewer.
+///
+/// Rough boundaries such as frosted glass reflect and refract about GGX microfacet normals. They
+/// are only sampled; `eval` and `pdf` treat every boundary as smooth.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dielectric {
+    /// Index of refraction of the solid.
+    pub ior: f32,
+    /// Color filtering the refracted light.
+    pub tint: Color,
+    /// Perceptual roughness of the boundary; zero for polished glass.
+    pub roughness: f32,
+}
+
+impl Default for Dielectric {
+    fn default() -> Self {
+        Dielectric {
+            ior: 1.5,
+            tint: Color::new(1., 1., 1.),
+            roughness: 0.,
+        }
+    }
+}
+
+impl Dielectric {
+    /// Returns the reflectance and the reflected and refracted directions about a microfacet
+    /// normal sampled with `u`. Directions ending up on the wrong side of the surface are `None`.
+    fn split(&self, normal: Vec3, view: Vec3, u: Vec2) -> (f32, Option<Vec3>, Option<Vec3>) {
+        let eta = if normal.dot(view) > 0. {
+            1. / self.ior
+        } else {
+            self.ior
+        };
+        let normal = facing(normal, view);
+
+        // facets turned away from the viewer are hidden by others; the mean normal stands in
+        let facet = Some(self.roughness)
+            .filter(|&roughness| roughness > 0.)
+            .map(|roughness| Ggx::new(roughness, Vec3::ZERO).sample_normal(normal, u))
+            .filter(|facet| facet.dot(view) > 0.)
+            .unwrap_or(normal);
+
+        let reflectance = fresnel_dielectric(view.dot(facet), eta);
+        let reflected = reflect(view, facet);
+        let refracted = refract(view, facet, eta);
+        (
+            reflectance,
+            (reflected.dot(normal) >= 0.).then_some(reflected),
+            refracted.filter(|refracted| refracted.dot(normal) <= 0.),
+        )
+    }
+
+    fn lobe(&self) -> Lobe {
+        if self.roughness > 0. {
+            Lobe::Glossy
+        } else {
+            Lobe::Specular
+        }
+    }
+}
+
+impl Bsdf for Dielectric {
+    fn eval(&self, _normal: Vec3, _view: Vec3, _light: Vec3) -> Color {
+        Color::default()
+    }
+
+    /// Chooses between reflection and refraction in proportion to the Fresnel reflectance, using
+    /// `u.x`, about a microfacet normal sampled with `u.y` and `u.z`.
+    fn sample(&self, normal: Vec3, view: Vec3, u: Vec3) -> Option<BsdfSample> {
+        let (reflectance, reflected, refracted) = self.split(normal, view, Vec2::new(u.y, u.z));
+
+        match refracted {
+            Some(refracted) if u.x >= reflectance => Some(BsdfSample {
+                direction: refracted,
+                weight: self.tint,
+                lobe: self.lobe(),
+            }),
+            _ => reflected.map(|reflected| BsdfSample {
+                direction: reflected,
+                weight: Color::new(1., 1., 1.),
+                lobe: self.lobe(),
+            }),
+        }
+    }
+
+    fn pdf(&self, _normal: Vec3, _view: Vec3, _light: Vec3) -> f32 {
+        0.
+    }
+
+    fn scatter(&self, normal: Vec3, view: Vec3, u: Vec2) -> Scatter {
+        let (reflectance, reflected, refracted) = self.split(normal, view, u);
+
+        Scatter {
+            reflection: reflected.map(|reflected| BsdfSample {
+                direction: reflected,
+                weight: Color::new(1., 1., 1.) * reflectance,
+                lobe: self.lobe(),
+            }),
+            transmission: refracted.map(|refracted| BsdfSample {
+                direction: refracted,
+                weight: self.tint * (1. - reflectance),
+                lobe: self.lobe(),
+            }),
+        }
+    }
+
+    fn glossy(&self) -> bool {
+        self.lobe() == Lobe::Glossy
+    }
+}
@@ -0,0 +1,30 @@
+use glam::Vec3;
+
+use crate::color::Color;
+
+use super::{Bsdf, BsdfSample};
+
+/// Surface glowing with `color` and reflecting no light, such as a lamp shade. The glow is seen
+/// by rays hitting the surface but doesn't light other surfaces; use lights for that.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Emissive {
+    pub color: Color,
+}
+
+impl Bsdf for Emissive {
+    fn eval(&self, _normal: Vec3, _view: Vec3, _light: Vec3) -> Color {
+        Color::default()
+    }
+
+    fn sample(&self, _normal: Vec3, _view: Vec3, _u: Vec3) -> Option<BsdfSample> {
+        None
+    }
+
+    fn pdf(&self, _normal: Vec3, _view: Vec3, _light: Vec3) -> f32 {
+        0.
+    }
+
+    fn emission(&self) -> Color {
+        self.color
+    }
+}
@@ -0,0 +1,39 @@
+use std::f32::consts::PI;
+
+use glam::Vec3;
+
+use crate::color::Color;
+
+use super::{cosine_direction, facing, to_world, Bsdf, BsdfSample, Lobe};
+
+/// Ideal diffuse reflector, scattering light equally in all directions.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Lambertian {
+    pub albedo: Color,
+}
+
+impl Bsdf for Lambertian {
+    fn eval(&self, normal: Vec3, view: Vec3, light: Vec3) -> Color {
+        let n_l = facing(normal, view).dot(light);
+        if n_l <= 0. {
+            return Color::default();
+        }
+        self.albedo * (n_l / PI)
+    }
+
+    fn sample(&self, normal: Vec3, view: Vec3, u: Vec3) -> Option<BsdfSample> {
+        let normal = facing(normal, view);
+        let light = to_world(normal, cosine_direction(u.y, u.z));
+
+        // the cosine and the density cancel
+        (normal.dot(light) > 0.).then_some(BsdfSample {
+            direction: light,
+            weight: self.albedo,
+            lobe: Lobe::Diffuse,
+        })
+    }
+
+    fn pdf(&self, normal: Vec3, view: Vec3, light: Vec3) -> f32 {
+        facing(normal, view).dot(light).max(0.) / PI
+    }
+}
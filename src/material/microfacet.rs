@@ -0,0 +1,102 @@
+use std::f32::consts::PI;
+
+use glam::{Vec2, Vec3};
+
+use super::{reflect, to_world};
+
+/// Smallest GGX width; perfectly smooth surfaces would make the distribution a delta function.
+const MIN_ALPHA: f32 = 1e-3;
+
+/// GGX microfacet reflection with Schlick's Fresnel term. `normal` must lie on the side of
+/// `view`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Ggx {
+    alpha: f32,
+    /// Reflectance at normal incidence.
+    f0: Vec3,
+}
+
+impl Ggx {
+    /// Lobe for the perceptual `roughness`; the GGX width is its square.
+    pub fn new(roughness: f32, f0: Vec3) -> Self {
+        Ggx {
+            alpha: (roughness * roughness).max(MIN_ALPHA),
+            f0,
+        }
+    }
+
+    pub fn fresnel(&self, v_h: f32) -> Vec3 {
+        self.f0 + (Vec3::ONE - self.f0) * (1. - v_h).clamp(0., 1.).powi(5)
+    }
+
+    /// BRDF times the cosine of the light direction.
+    pub fn eval(&self, normal: Vec3, view: Vec3, light: Vec3) -> Vec3 {
+        let n_v = normal.dot(view);
+        let n_l = normal.dot(light);
+        if n_v <= 0. || n_l <= 0. {
+            return Vec3::ZERO;
+        }
+
+        let half = (view + light).normalize();
+        self.fresnel(view.dot(half))
+            * (distribution(normal.dot(half), self.alpha) * self.shadowing(n_v, n_l) / (4. * n_v))
+    }
+
+    /// Samples a microfacet normal in proportion to the distribution times its cosine.
+    pub fn sample_normal(&self, normal: Vec3, u: Vec2) -> Vec3 {
+        let cos_theta = ((1. - u.x) / (1. + (self.alpha * self.alpha - 1.) * u.x)).sqrt();
+        let sin_theta = (1. - cos_theta * cos_theta).max(0.).sqrt();
+        let phi = 2. * PI * u.y;
+        to_world(
+            normal,
+            Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta),
+        )
+    }
+
+    /// Samples a light direction by reflecting `view` about a microfacet normal from
+    /// [`Ggx::sample_normal`].
+    pub fn sample(&self, normal: Vec3, view: Vec3, u: Vec2) -> Option<Vec3> {
+        let half = self.sample_normal(normal, u);
+        let light = reflect(view, half);
+        (view.dot(half) > 0. && normal.dot(light) > 0.).then_some(light)
+    }
+
+    /// Density of [`Ggx::sample`] choosing `light`, per solid angle.
+    pub fn pdf(&self, normal: Vec3, view: Vec3, light: Vec3) -> f32 {
+        let half = (view + light).normalize();
+        let n_h = normal.dot(half);
+        let v_h = view.dot(half);
+        if v_h <= 0. {
+            return 0.;
+        }
+
+        distribution(n_h, self.alpha) * n_h / (4. * v_h)
+    }
+
+    /// [`Ggx::eval`] over [`Ggx::pdf`] for a sampled direction, where the distribution cancels.
+    pub fn weight(&self, normal: Vec3, view: Vec3, light: Vec3) -> Vec3 {
+        let half = (view + light).normalize();
+        let n_v = normal.dot(view);
+        let n_l = normal.dot(light);
+        let v_h = view.dot(half);
+
+        self.fresnel(v_h) * (self.shadowing(n_v, n_l) * v_h / (n_v * normal.dot(half)))
+    }
+
+    /// Separable Smith shadowing-masking term.
+    fn shadowing(&self, n_v: f32, n_l: f32) -> f32 {
+        let a2 = self.alpha * self.alpha;
+        let g1 = |n_x: f32| 2. * n_x / (n_x + (a2 + (1. - a2) * n_x * n_x).sqrt());
+        g1(n_v) * g1(n_l)
+    }
+}
+
+/// GGX normal distribution for the cosine `n_h` between the normal and the half vector.
+fn distribution(n_h: f32, alpha: f32) -> f32 {
+    if n_h <= 0. {
+        return 0.;
+    }
+    let a2 = alpha * alpha;
+    let d = n_h * n_h * (a2 - 1.) + 1.;
+    a2 / (PI * d * d)
+}
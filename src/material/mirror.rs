@@ -0,0 +1,36 @@
+use glam::{Vec2, Vec3};
+
+use crate::color::Color;
+
+use super::{facing, reflect, Bsdf, BsdfSample, Lobe, Scatter};
+
+/// Perfect mirror, reflecting `color` of the light in the mirror direction.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Mirror {
+    pub color: Color,
+}
+
+impl Bsdf for Mirror {
+    fn eval(&self, _normal: Vec3, _view: Vec3, _light: Vec3) -> Color {
+        Color::default()
+    }
+
+    fn sample(&self, normal: Vec3, view: Vec3, _u: Vec3) -> Option<BsdfSample> {
+        Some(BsdfSample {
+            direction: reflect(view, facing(normal, view)),
+            weight: self.color,
+            lobe: Lobe::Specular,
+        })
+    }
+
+    fn pdf(&self, _normal: Vec3, _view: Vec3, _light: Vec3) -> f32 {
+        0.
+    }
+
+    fn scatter(&self, normal: Vec3, view: Vec3, _u: Vec2) -> Scatter {
+        Scatter {
+            reflection: self.sample(normal, view, Vec3::ZERO),
+            transmission: None,
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::f32::consts::PI;
+
+use glam::{Vec2, Vec3};
+
+use crate::color::Color;
+
+use super::{cosine_direction, facing, microfacet::Ggx, to_world, Bsdf, BsdfSample, Lobe, Scatter};
+
+/// Reflectance at normal incidence of dielectrics, about that of glass and plastics.
+const DIELECTRIC_F0: f32 = 0.04;
+
+/// Metallic-roughness material as used by glTF and most PBR authoring tools: a Lambertian base
+/// layer under a GGX microfacet specular layer with Schlick's Fresnel term. Metals have no
+/// diffuse layer and tint their specular reflection with the base color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PbrMaterial {
+    pub base_color: Color,
+    pub metallic: f32,
+    /// Perceptual roughness; the GGX width is its square.
+    pub roughness: f32,
+    pub emission: Color,
+}
+
+impl Default for PbrMaterial {
+    fn default() -> Self {
+        PbrMaterial {
+            base_color: Color::new(0.8, 0.8, 0.8),
+            metallic: 0.,
+            roughness: 0.5,
+            emission: Color::default(),
+        }
+    }
+}
+
+impl PbrMaterial {
+    fn specular(&self) -> Ggx {
+        let f0 = Vec3::splat(DIELECTRIC_F0).lerp(self.base_color.into(), self.metallic);
+        Ggx::new(self.roughness, f0)
+    }
+
+    /// Diffuse BRDF times cosine. The base only receives the light not reflected by the specular
+    /// layer, so the total never exceeds the incoming light.
+    fn diffuse(&self, specular: &Ggx, normal: Vec3, view: Vec3, light: Vec3) -> Vec3 {
+        let n_l = normal.dot(light);
+        if n_l <= 0. {
+            return Vec3::ZERO;
+        }
+
+        let fresnel = specular.fresnel(view.dot((view + light).normalize()));
+        let base: Vec3 = self.base_color.into();
+        (Vec3::ONE - fresnel) * base * ((1. - self.metallic) / PI * n_l)
+    }
+
+    /// Share of the samples spent on the specular lobe; metals have nothing else.
+    fn specular_probability(&self) -> f32 {
+        0.5 * (1. + self.metallic.clamp(0., 1.))
+    }
+}
+
+impl Bsdf for PbrMaterial {
+    fn eval(&self, normal: Vec3, view: Vec3, light: Vec3) -> Color {
+        let normal = facing(normal, view);
+        let specular = self.specular();
+
+        (self.diffuse(&specular, normal, view, light) + specular.eval(normal, view, light)).into()
+    }
+
+    /// Chooses the lobe with `u.x` and the direction within it with `u.y` and `u.z`.
+    fn sample(&self, normal: Vec3, view: Vec3, u: Vec3) -> Option<BsdfSample> {
+        let normal = facing(normal, view);
+
+        let (light, lobe) = if u.x < self.specular_probability() {
+            let light = self.specular().sample(normal, view, Vec2::new(u.y, u.z))?;
+            (light, Lobe::Glossy)
+        } else {
+            (to_world(normal, cosine_direction(u.y, u.z)), Lobe::Diffuse)
+        };
+
+        let pdf = self.pdf(normal, view, light);
+        (pdf > 0.).then(|| BsdfSample {
+            direction: light,
+            weight: self.eval(normal, view, light) * (1. / pdf),
+            lobe,
+        })
+    }
+
+    fn pdf(&self, normal: Vec3, view: Vec3, light: Vec3) -> f32 {
+        let normal = facing(normal, view);
+        let n_l = normal.dot(light);
+        if n_l <= 0. {
+            return 0.;
+        }
+
+        let p = self.specular_probability();
+        p * self.specular().pdf(normal, view, light) + (1. - p) * n_l / PI
+    }
+
+    /// Follows the specular lobe alone; the base is lit by the lights directly.
+    fn scatter(&self, normal: Vec3, view: Vec3, u: Vec2) -> Scatter {
+        let normal = facing(normal, view);
+        let specular = self.specular();
+
+        Scatter {
+            reflection: specular.sample(normal, view, u).map(|light| BsdfSample {
+                direction: light,
+                weight: specular.weight(normal, view, light).into(),
+                lobe: Lobe::Glossy,
+            }),
+            transmission: None,
+        }
+    }
+
+    fn glossy(&self) -> bool {
+        true
+    }
+
+    fn emission(&self) -> Color {
+        self.emission
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn samples_cover_the_hemisphere() {
+        // the diffuse lobe covers the hemisphere, so the inverse densities of the samples add up
+        // to its solid angle
+        let view = Vec3::new(0.6, 0., 0.8);
+        let mut rng = SmallRng::seed_from_u64(1);
+
+        for roughness in [0.1, 0.5, 1.] {
+            let material = PbrMaterial {
+                roughness,
+                ..Default::default()
+            };
+
+            let samples = 100_000;
+            let solid_angle = (0..samples)
+                .filter_map(|_| {
+                    let u = Vec3::new(rng.gen(), rng.gen(), rng.gen());
+                    material.sample(Vec3::Z, view, u)
+                })
+                .map(|sample| 1. / material.pdf(Vec3::Z, view, sample.direction))
+                .sum::<f32>()
+                / samples as f32;
+
+            assert!(
+                (solid_angle - 2. * PI).abs() < 0.1,
+                "roughness {roughness} covers {solid_angle}"
+            );
+        }
+    }
+
+    #[test]
+    fn scattered_reflection_matches_the_brdf() {
+        // metals have no diffuse lobe, so the whole BRDF is followed
+        let material = PbrMaterial {
+            metallic: 1.,
+            ..Default::default()
+        };
+        let view = Vec3::new(0.6, 0., 0.8);
+
+        let mut rng = SmallRng::seed_from_u64(2);
+        let sample = std::iter::repeat_with(|| Vec2::new(rng.gen(), rng.gen()))
+            .find_map(|u| material.scatter(Vec3::Z, view, u).reflection)
+            .unwrap();
+
+        let light = sample.direction;
+        let expected =
+            material.eval(Vec3::Z, view, light) * (1. / material.pdf(Vec3::Z, view, light));
+        assert!((Vec3::from(sample.weight) - Vec3::from(expected)).length() < 1e-4);
+    }
+}
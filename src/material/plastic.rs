@@ -0,0 +1,88 @@
+use std::f32::consts::PI;
+
+use glam::{Vec2, Vec3};
+
+use crate::color::Color;
+
+use super::{
+    cosine_direction, facing, fresnel_dielectric, reflect, to_world, Bsdf, BsdfSample, Lobe,
+    Scatter,
+};
+
+/// Diffuse base under a smooth clear coat, such as plastic or varnished wood. The coat reflects
+/// according to Fresnel's equations and the base scatters the light the coat lets through.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plastic {
+    pub diffuse: Color,
+    /// Index of refraction of the coat.
+    pub ior: f32,
+}
+
+impl Default for Plastic {
+    fn default() -> Self {
+        Plastic {
+            diffuse: Color::new(0.8, 0.8, 0.8),
+            ior: 1.5,
+        }
+    }
+}
+
+impl Plastic {
+    /// Reflectance of the coat for light at the angle with cosine `cos`.
+    fn coat(&self, cos: f32) -> f32 {
+        fresnel_dielectric(cos, 1. / self.ior)
+    }
+}
+
+impl Bsdf for Plastic {
+    /// Only the base is reached by a given direction; light passes the coat on the way in and out.
+    fn eval(&self, normal: Vec3, view: Vec3, light: Vec3) -> Color {
+        let normal = facing(normal, view);
+        let n_l = normal.dot(light);
+        if n_l <= 0. {
+            return Color::default();
+        }
+
+        let transmitted = (1. - self.coat(normal.dot(view))) * (1. - self.coat(n_l));
+        self.diffuse * (transmitted * n_l / PI)
+    }
+
+    /// Chooses between the coat and the base in proportion to the coat's reflectance.
+    fn sample(&self, normal: Vec3, view: Vec3, u: Vec3) -> Option<BsdfSample> {
+        let normal = facing(normal, view);
+
+        if u.x < self.coat(normal.dot(view)) {
+            return Some(BsdfSample {
+                direction: reflect(view, normal),
+                weight: Color::new(1., 1., 1.),
+                lobe: Lobe::Specular,
+            });
+        }
+
+        let light = to_world(normal, cosine_direction(u.y, u.z));
+        let n_l = normal.dot(light);
+        (n_l > 0.).then(|| BsdfSample {
+            direction: light,
+            weight: self.diffuse * (1. - self.coat(n_l)),
+            lobe: Lobe::Diffuse,
+        })
+    }
+
+    fn pdf(&self, normal: Vec3, view: Vec3, light: Vec3) -> f32 {
+        let normal = facing(normal, view);
+        (1. - self.coat(normal.dot(view))) * normal.dot(light).max(0.) / PI
+    }
+
+    fn scatter(&self, normal: Vec3, view: Vec3, _u: Vec2) -> Scatter {
+        let normal = facing(normal, view);
+
+        Scatter {
+            reflection: Some(BsdfSample {
+                direction: reflect(view, normal),
+                weight: Color::new(1., 1., 1.) * self.coat(normal.dot(view)),
+                lobe: Lobe::Specular,
+            }),
+            transmission: None,
+        }
+    }
+}
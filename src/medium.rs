@@ -0,0 +1,231 @@
+use std::f32::consts::PI;
+
+use glam::Vec3;
+
+use crate::{aabb::Aabb, color::Color, ray::Ray};
+
+/// Transmittance below which a medium is considered opaque and no longer marched through.
+const OPAQUE: f32 = 1e-3;
+
+/// Homogeneous participating medium such as fog, smoke or murky water. The coefficients are the
+/// fractions of light absorbed and scattered per world unit travelled.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Medium {
+    pub absorption: Color,
+    pub scattering: Color,
+    /// Henyey–Greenstein asymmetry between -1 and 1: positive values scatter light forwards,
+    /// negative ones backwards and zero evenly in all directions.
+    pub asymmetry: f32,
+}
+
+impl Medium {
+    /// Fraction of light absorbed or scattered away per world unit.
+    pub fn extinction(&self) -> Color {
+        self.absorption + self.scattering
+    }
+
+    /// Henyey–Greenstein phase function: the density per solid angle of light being scattered
+    /// by an angle with cosine `cos_theta` from its direction of travel.
+    pub fn phase(&self, cos_theta: f32) -> f32 {
+        let g = self.asymmetry;
+        let denominator = 1. + g * g - 2. * g * cos_theta;
+        (1. - g * g) / (4. * PI * denominator * denominator.sqrt())
+    }
+
+    /// Fraction of the light arriving at a step of `length` that the step scatters, taking the
+    /// attenuation along the step into account. Multiplied by the phase function, it weighs the
+    /// light scattered towards the viewer.
+    pub fn scattered_over(&self, length: f32) -> Color {
+        let extinction: Vec3 = self.extinction().into();
+        let scattering: Vec3 = self.scattering.into();
+
+        let absorbed = Vec3::ONE - (-extinction * length).exp();
+        Vec3::select(
+            extinction.cmpgt(Vec3::ZERO),
+            scattering / extinction * absorbed,
+            Vec3::ZERO,
+        )
+        .into()
+    }
+
+    /// Distance after which the medium lets through less than [`OPAQUE`] of the light in all
+    /// channels that interact with it.
+    fn reach(&self) -> f32 {
+        let extinction: Vec3 = self.extinction().into();
+        let weakest =
+            Vec3::select(extinction.cmpgt(Vec3::ZERO), extinction, Vec3::INFINITY).min_element();
+        -OPAQUE.ln() / weakest
+    }
+}
+
+/// Region of space filled by a [`Volume`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bounds {
+    /// The whole scene, as for global fog.
+    Everywhere,
+    Sphere {
+        center: Vec3,
+        radius: f32,
+    },
+    Box(Aabb),
+}
+
+impl Bounds {
+    /// Returns the distances along `ray` at which it enters and leaves the region, limited to
+    /// the part between its origin and `far`.
+    pub fn clip(&self, ray: &Ray, far: f32) -> Option<(f32, f32)> {
+        let (near, exit) = match *self {
+            Bounds::Everywhere => (0., far),
+            Bounds::Sphere { center, radius } => {
+                let oc = ray.origin - center;
+                let a = ray.direction.length_squared();
+                let b = oc.dot(ray.direction);
+                let c = oc.length_squared() - radius * radius;
+
+                let discriminant = b * b - a * c;
+                if discriminant < 0. {
+                    return None;
+                }
+                let root = discriminant.sqrt();
+                ((-b - root) / a, (-b + root) / a)
+            }
+            Bounds::Box(aabb) => {
+                let inv_direction = ray.direction.recip();
+                let t1 = (aabb.min - ray.origin) * inv_direction;
+                let t2 = (aabb.max - ray.origin) * inv_direction;
+                (t1.min(t2).max_element(), t1.max(t2).min_element())
+            }
+        };
+
+        let (near, exit) = (near.max(0.), exit.min(far));
+        (near < exit).then_some((near, exit))
+    }
+}
+
+/// A medium filling a region of the scene.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Volume {
+    pub medium: Medium,
+    pub bounds: Bounds,
+}
+
+impl Volume {
+    /// Returns the part of `ray` up to `far` worth marching through to gather scattered light:
+    /// the part inside the volume, up to where it turns opaque.
+    pub fn march_interval(&self, ray: &Ray, far: f32) -> Option<(f32, f32)> {
+        let (near, exit) = self.bounds.clip(ray, far)?;
+        Some((near, exit.min(near + self.medium.reach())))
+    }
+}
+
+/// Fraction of the light travelling along `ray` that is neither absorbed nor scattered away by
+/// `volumes` within `distance` of its origin, following the Beer–Lambert law.
+pub fn transmittance(volumes: &[Volume], ray: &Ray, distance: f32) -> Color {
+    let depth = volumes
+        .iter()
+        .filter_map(|volume| {
+            let (near, exit) = volume.bounds.clip(ray, distance)?;
+            Some(Vec3::from(volume.medium.extinction()) * (exit - near))
+        })
+        .sum::<Vec3>();
+    (-depth).exp().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fog(density: f32) -> Medium {
+        Medium {
+            absorption: Color::new(density, density, density) * 0.5,
+            scattering: Color::new(density, density, density) * 0.5,
+            asymmetry: 0.,
+        }
+    }
+
+    #[test]
+    fn phase_function_is_normalized() {
+        for asymmetry in [-0.7, 0., 0.3, 0.9] {
+            let medium = Medium {
+                asymmetry,
+                ..fog(1.)
+            };
+
+            // integrate over the sphere in slices of equal cosine, which have equal solid angle
+            let slices = 100_000;
+            let total = (0..slices)
+                .map(|i| {
+                    let cos_theta = -1. + 2. * (i as f32 + 0.5) / slices as f32;
+                    medium.phase(cos_theta) * 4. * PI / slices as f32
+                })
+                .sum::<f32>();
+            assert!(
+                (total - 1.).abs() < 1e-2,
+                "{asymmetry} integrates to {total}"
+            );
+        }
+
+        let forward = Medium {
+            asymmetry: 0.5,
+            ..fog(1.)
+        };
+        assert!(forward.phase(1.) > forward.phase(-1.));
+    }
+
+    #[test]
+    fn clips_rays_to_the_bounds() {
+        let ray = Ray::new(Vec3::new(0., 0., 5.), Vec3::NEG_Z, 0.);
+
+        let sphere = Bounds::Sphere {
+            center: Vec3::ZERO,
+            radius: 1.,
+        };
+        assert_eq!(sphere.clip(&ray, 100.), Some((4., 6.)));
+        assert_eq!(sphere.clip(&ray, 5.), Some((4., 5.)));
+        assert_eq!(sphere.clip(&ray, 3.), None);
+
+        let cube = Bounds::Box(Aabb::new(Vec3::splat(-1.), Vec3::splat(1.)));
+        assert_eq!(cube.clip(&ray, 100.), Some((4., 6.)));
+        let inside = Ray::new(Vec3::ZERO, Vec3::X, 0.);
+        assert_eq!(cube.clip(&inside, 100.), Some((0., 1.)));
+        let beside = Ray::new(Vec3::new(3., 0., 5.), Vec3::NEG_Z, 0.);
+        assert_eq!(cube.clip(&beside, 100.), None);
+
+        assert_eq!(Bounds::Everywhere.clip(&ray, 7.), Some((0., 7.)));
+    }
+
+    #[test]
+    fn attenuates_exponentially_with_distance() {
+        let ray = Ray::new(Vec3::new(0., 0., 5.), Vec3::NEG_Z, 0.);
+        let volumes = [
+            Volume {
+                medium: fog(0.5),
+                bounds: Bounds::Sphere {
+                    center: Vec3::ZERO,
+                    radius: 1.,
+                },
+            },
+            Volume {
+                medium: fog(0.1),
+                bounds: Bounds::Everywhere,
+            },
+        ];
+
+        // ten units of the global fog, two of them through the sphere
+        let expected = (-0.1f32 * 10. - 0.5 * 2.).exp();
+        assert!((transmittance(&volumes, &ray, 10.).r() - expected).abs() < 1e-6);
+        assert_eq!(
+            transmittance(&volumes[..1], &ray, 4.),
+            Color::new(1., 1., 1.)
+        );
+    }
+
+    #[test]
+    fn scattering_over_a_step_saturates() {
+        let medium = fog(2.);
+        // a thin step scatters in proportion to its length, a thick one at most the albedo
+        assert!((medium.scattered_over(1e-3).r() - 1e-3).abs() < 1e-5);
+        assert!((medium.scattered_over(100.).r() - 0.5).abs() < 1e-5);
+        assert_eq!(Medium::default().scattered_over(1.), Color::default());
+    }
+}
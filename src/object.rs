@@ -1,21 +1,88 @@
+pub mod bezier;
+mod boundary;
+pub mod cone;
+pub mod csg;
+pub mod cuboid;
+pub mod cylinder;
+pub mod disk;
+mod frame;
+pub mod group;
+pub mod instance;
+pub mod mesh;
+pub mod moving;
+pub mod plane;
+pub mod sdf;
 pub mod sphere;
+pub mod torus;
 pub mod triangle;
 
 use enum_dispatch::enum_dispatch;
-use glam::Vec3;
+use glam::{Vec2, Vec3};
 
-use crate::surface::SurfaceMaterial;
+use crate::{aabb::Aabb, color::Color, object_store::MaterialId, ray::Ray};
 
 #[derive(Debug, Clone, PartialEq)]
 #[enum_dispatch]
 pub enum Object {
     Sphere(sphere::Sphere),
     Triangle(triangle::Triangle),
+    Moving(moving::Moving),
+    Group(group::Group),
+    Instance(instance::Instance),
+    Mesh(mesh::Mesh),
+    Plane(plane::Plane),
+    Cuboid(cuboid::Cuboid),
+    Disk(disk::Disk),
+    Cylinder(cylinder::Cylinder),
+    Cone(cone::Cone),
+    Torus(torus::Torus),
+    Csg(csg::Csg),
+    DistanceField(sdf::DistanceField),
+    BezierPatch(bezier::BezierPatch),
+}
+
+/// Intersection of a ray with the surface of an object.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    pub distance: f32,
+    pub normal: Vec3,
+    pub uv: Vec2,
+    pub material: MaterialId,
+    /// Interpolated vertex color, shaded in place of the material's diffuse color.
+    pub color: Option<Color>,
+}
+
+/// Stretch of a ray's line that lies inside a solid, bounded by the hits where the line enters and
+/// leaves it. Either end may lie behind the ray origin or at infinity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub enter: Hit,
+    pub exit: Hit,
 }
 
 #[enum_dispatch(Object)]
 pub trait Intersectable {
-    fn intersect(&self, pos: Vec3, dir: Vec3) -> Option<f32>;
-    fn normal(&self, pos: Vec3) -> Vec3;
-    fn surface(&self) -> &SurfaceMaterial;
+    fn intersect(&self, ray: &Ray) -> Option<Hit>;
+    fn bounds(&self) -> Aabb;
+
+    /// Appends the sorted, disjoint intervals along the whole line of `ray` that lie inside the
+    /// object. Only solids report intervals; surfaces such as triangles and disks don't enclose a
+    /// volume and report none. Meshes are taken to be closed, with their faces wound
+    /// counter-clockwise seen from outside.
+    fn intervals(&self, _ray: &Ray, _intervals: &mut Vec<Interval>) {}
+}
+
+/// Returns `ray` moved back along its line to start outside of the [bounded](Aabb::is_bounded)
+/// `bounds`, so that everything the line crosses within them lies ahead of it, along with the
+/// distance it was moved back by. The ray is moved no further back than to where the line enters
+/// the bounds, plus their size, to keep the distances along it precise.
+fn line_behind(bounds: Aabb, ray: &Ray) -> (Ray, f32) {
+    let inv_direction = ray.direction.recip();
+    let t1 = (bounds.min - ray.origin) * inv_direction;
+    let t2 = (bounds.max - ray.origin) * inv_direction;
+    let enter = t1.min(t2).max_element();
+
+    let margin = (bounds.max - bounds.min).length() / ray.direction.length();
+    let back = (-enter).max(0.) + margin;
+    (Ray::new(ray.at(-back), ray.direction, ray.time), back)
 }
@@ -0,0 +1,225 @@
+use glam::{Vec2, Vec3};
+
+use crate::{aabb::Aabb, bvh::Bvh, consts::MIN_HIT_DISTANCE, object_store::MaterialId, ray::Ray};
+
+use super::{Hit, Intersectable};
+
+/// Number of sub-patches per parameter direction used to seed the root finder.
+const RESOLUTION: usize = 8;
+const MAX_ITERATIONS: u32 = 8;
+const TOLERANCE: f32 = 1e-5;
+
+/// Bicubic Bézier patch, intersected directly by Newton iteration on the patch parameters.
+/// `control[v][u]` is the control point in row `v` and column `u`; texture coordinates are the
+/// patch parameters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BezierPatch {
+    control: [[Vec3; 4]; 4],
+    sub_patches: Vec<SubPatch>,
+    bvh: Bvh,
+    pub material: MaterialId,
+}
+
+/// Parameter range of a piece of the patch, bounded by the convex hull of its control points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SubPatch {
+    min: Vec2,
+    max: Vec2,
+    bounds: Aabb,
+}
+
+impl BezierPatch {
+    pub fn new(control: [[Vec3; 4]; 4], material: MaterialId) -> Self {
+        let step = 1. / RESOLUTION as f32;
+        let mut sub_patches = Vec::with_capacity(RESOLUTION * RESOLUTION);
+
+        for j in 0..RESOLUTION {
+            let (v0, v1) = (j as f32 * step, (j + 1) as f32 * step);
+            let columns = transpose(control).map(|column| segment(column, v0, v1));
+
+            for i in 0..RESOLUTION {
+                let (u0, u1) = (i as f32 * step, (i + 1) as f32 * step);
+                let hull = transpose(columns).map(|row| segment(row, u0, u1));
+
+                sub_patches.push(SubPatch {
+                    min: Vec2::new(u0, v0),
+                    max: Vec2::new(u1, v1),
+                    bounds: Aabb::from_points(hull.into_iter().flatten()),
+                });
+            }
+        }
+
+        let bvh = Bvh::build(&mut sub_patches, |sub_patch| sub_patch.bounds);
+
+        BezierPatch {
+            control,
+            sub_patches,
+            bvh,
+            material,
+        }
+    }
+
+    /// Returns the point and partial derivatives with respect to `u` and `v` at `(u, v)`.
+    fn evaluate(&self, u: f32, v: f32) -> (Vec3, Vec3, Vec3) {
+        let (bu, du) = bernstein(u);
+        let (bv, dv) = bernstein(v);
+
+        let mut point = Vec3::ZERO;
+        let mut tangent_u = Vec3::ZERO;
+        let mut tangent_v = Vec3::ZERO;
+        for (j, row) in self.control.iter().enumerate() {
+            for (i, control) in row.iter().enumerate() {
+                point += *control * bu[i] * bv[j];
+                tangent_u += *control * du[i] * bv[j];
+                tangent_v += *control * bu[i] * dv[j];
+            }
+        }
+
+        (point, tangent_u, tangent_v)
+    }
+
+    /// Solves for the patch parameters where the ray crosses the surface, treating the ray as the
+    /// intersection of two planes and starting from the center of `sub_patch`.
+    fn solve(&self, ray: &Ray, sub_patch: &SubPatch) -> Option<(f32, Vec2)> {
+        let n1 = ray.direction.any_orthonormal_vector();
+        let n2 = ray.direction.cross(n1);
+
+        let mut uv = (sub_patch.min + sub_patch.max) / 2.;
+        for _ in 0..MAX_ITERATIONS {
+            let (point, tangent_u, tangent_v) = self.evaluate(uv.x, uv.y);
+            let offset = point - ray.origin;
+            let error = Vec2::new(n1.dot(offset), n2.dot(offset));
+
+            if error.length() < TOLERANCE * (1. + offset.length()) {
+                let margin = Vec2::splat(TOLERANCE);
+                let inside = uv.cmpge(sub_patch.min - margin).all()
+                    && uv.cmple(sub_patch.max + margin).all();
+                let distance = offset.dot(ray.direction);
+
+                return (inside && distance > MIN_HIT_DISTANCE).then_some((distance, uv));
+            }
+
+            let (a, b) = (n1.dot(tangent_u), n1.dot(tangent_v));
+            let (c, d) = (n2.dot(tangent_u), n2.dot(tangent_v));
+            let det = a * d - b * c;
+            if det.abs() < f32::EPSILON {
+                return None;
+            }
+
+            uv -= Vec2::new(d * error.x - b * error.y, a * error.y - c * error.x) / det;
+        }
+
+        None
+    }
+}
+
+impl Intersectable for BezierPatch {
+    fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        let (distance, uv) = self
+            .bvh
+            .closest_hit(ray, |i| self.solve(ray, &self.sub_patches[i]))?;
+
+        let (_, tangent_u, tangent_v) = self.evaluate(uv.x, uv.y);
+        let normal = tangent_u.cross(tangent_v).normalize_or_zero();
+
+        Some(Hit {
+            distance,
+            normal,
+            uv,
+            material: self.material,
+            color: None,
+        })
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.bvh.bounds()
+    }
+}
+
+/// Cubic Bernstein basis functions and their derivatives at `t`.
+fn bernstein(t: f32) -> ([f32; 4], [f32; 4]) {
+    let s = 1. - t;
+    (
+        [s * s * s, 3. * t * s * s, 3. * t * t * s, t * t * t],
+        [
+            -3. * s * s,
+            3. * s * (s - 2. * t),
+            3. * t * (2. * s - t),
+            3. * t * t,
+        ],
+    )
+}
+
+/// Control points of the piece of a cubic Bézier curve between parameters `t0` and `t1`.
+fn segment(curve: [Vec3; 4], t0: f32, t1: f32) -> [Vec3; 4] {
+    let (head, _) = split(curve, t1);
+    if t1 <= 0. {
+        return head;
+    }
+    split(head, t0 / t1).1
+}
+
+/// Splits a cubic Bézier curve at `t` using de Casteljau's algorithm.
+fn split([p0, p1, p2, p3]: [Vec3; 4], t: f32) -> ([Vec3; 4], [Vec3; 4]) {
+    let p01 = p0.lerp(p1, t);
+    let p12 = p1.lerp(p2, t);
+    let p23 = p2.lerp(p3, t);
+    let p012 = p01.lerp(p12, t);
+    let p123 = p12.lerp(p23, t);
+    let p0123 = p012.lerp(p123, t);
+
+    ([p0, p01, p012, p0123], [p0123, p123, p23, p3])
+}
+
+fn transpose(rows: [[Vec3; 4]; 4]) -> [[Vec3; 4]; 4] {
+    std::array::from_fn(|i| std::array::from_fn(|j| rows[j][i]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(height: impl Fn(usize, usize) -> f32) -> [[Vec3; 4]; 4] {
+        std::array::from_fn(|j| {
+            std::array::from_fn(|i| Vec3::new(i as f32 / 3., height(i, j), j as f32 / 3.))
+        })
+    }
+
+    #[test]
+    fn flat_patch() {
+        let patch = BezierPatch::new(grid(|_, _| 0.), MaterialId(0));
+        let hit = patch
+            .intersect(&Ray::new(Vec3::new(0.3, 1., 0.7), Vec3::NEG_Y, 0.))
+            .unwrap();
+
+        assert!((hit.distance - 1.).abs() < 1e-4);
+        assert!((hit.normal - Vec3::NEG_Y).length() < 1e-4);
+        assert!((hit.uv - Vec2::new(0.3, 0.7)).length() < 1e-4);
+
+        let miss = Ray::new(Vec3::new(1.5, 1., 0.5), Vec3::NEG_Y, 0.);
+        assert!(patch.intersect(&miss).is_none());
+    }
+
+    #[test]
+    fn curved_patch() {
+        // raised inner control points bulge the center of the patch upwards
+        let patch = BezierPatch::new(
+            grid(|i, j| {
+                if (1..3).contains(&i) && (1..3).contains(&j) {
+                    1.
+                } else {
+                    0.
+                }
+            }),
+            MaterialId(0),
+        );
+        let ray = Ray::new(Vec3::new(0.4, 2., 0.6), Vec3::NEG_Y, 0.);
+        let hit = patch.intersect(&ray).unwrap();
+
+        // the inner basis functions sum to 0.72 at both 0.4 and 0.6
+        assert!((hit.distance - (2. - 0.72 * 0.72)).abs() < 1e-4);
+
+        let (point, _, _) = patch.evaluate(hit.uv.x, hit.uv.y);
+        assert!((point - ray.at(hit.distance)).length() < 1e-4);
+    }
+}
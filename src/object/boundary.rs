@@ -0,0 +1,45 @@
+use glam::{Vec2, Vec3};
+
+use crate::consts::MIN_HIT_DISTANCE;
+
+type Crossing = (f32, Vec3, Vec2);
+
+/// Points where the whole line of a ray crosses the surface of a convex primitive, as distance,
+/// local normal and texture coordinates.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct Boundary {
+    crossings: [Crossing; 4],
+    len: usize,
+}
+
+impl Boundary {
+    pub fn push(&mut self, distance: f32, normal: Vec3, uv: Vec2) {
+        self.crossings[self.len] = (distance, normal, uv);
+        self.len += 1;
+    }
+
+    fn crossings(&self) -> &[Crossing] {
+        &self.crossings[..self.len]
+    }
+
+    /// The closest crossing in front of the ray origin.
+    pub fn first(&self) -> Option<Crossing> {
+        self.crossings()
+            .iter()
+            .filter(|(distance, _, _)| *distance > MIN_HIT_DISTANCE)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .copied()
+    }
+
+    /// The crossings where the line enters and leaves the primitive. Being convex, it is inside
+    /// the primitive everywhere in between.
+    pub fn span(&self) -> Option<(Crossing, Crossing)> {
+        if self.len < 2 {
+            return None;
+        }
+
+        let enter = self.crossings().iter().min_by(|a, b| a.0.total_cmp(&b.0))?;
+        let exit = self.crossings().iter().max_by(|a, b| a.0.total_cmp(&b.0))?;
+        Some((*enter, *exit))
+    }
+}
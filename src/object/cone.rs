@@ -0,0 +1,144 @@
+use std::f32::consts::PI;
+
+use glam::{Vec2, Vec3};
+
+use crate::{aabb::Aabb, object_store::MaterialId, ray::Ray, solver::solve_quadratic};
+
+use super::{boundary::Boundary, disk::intersect_cap, frame::Frame, Hit, Intersectable, Interval};
+
+/// Cone with a capped circular base, narrowing to its apex `height` along `axis`. Texture
+/// coordinates are laid out like those of a [`Cylinder`](super::cylinder::Cylinder).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cone {
+    pub radius: f32,
+    pub height: f32,
+    pub material: MaterialId,
+    frame: Frame,
+}
+
+impl Cone {
+    pub fn new(base: Vec3, axis: Vec3, radius: f32, height: f32, material: MaterialId) -> Self {
+        Cone {
+            radius,
+            height,
+            material,
+            frame: Frame::from_axis(base, axis),
+        }
+    }
+
+    fn boundary(&self, local: &Ray) -> Boundary {
+        let (o, d) = (local.origin.as_dvec3(), local.direction.as_dvec3());
+        let height = self.height as f64;
+
+        // x² + z² = k² (h - y)²
+        let k2 = (self.radius as f64 / height).powi(2);
+        let h = height - o.y;
+
+        let mut boundary = Boundary::default();
+
+        let roots = solve_quadratic([
+            o.x * o.x + o.z * o.z - k2 * h * h,
+            2. * (o.x * d.x + o.z * d.z + k2 * h * d.y),
+            d.x * d.x + d.z * d.z - k2 * d.y * d.y,
+        ]);
+        for &t in roots.iter() {
+            let t = t as f32;
+            let p = local.at(t);
+            if (0. ..=self.height).contains(&p.y) {
+                let normal = Vec3::new(p.x, k2 as f32 * (self.height - p.y), p.z).normalize();
+                let uv = Vec2::new(0.5 + p.z.atan2(p.x) / (2. * PI), p.y / self.height);
+                boundary.push(t, normal, uv);
+            }
+        }
+
+        if let Some((t, uv)) = intersect_cap(local, 0., self.radius) {
+            boundary.push(t, Vec3::NEG_Y, uv);
+        }
+
+        boundary
+    }
+
+    fn hit(&self, (distance, normal, uv): (f32, Vec3, Vec2)) -> Hit {
+        Hit {
+            distance,
+            normal: self.frame.world_normal(normal),
+            uv,
+            material: self.material,
+            color: None,
+        }
+    }
+}
+
+impl Intersectable for Cone {
+    fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        let local = self.frame.local_ray(ray);
+        self.boundary(&local)
+            .first()
+            .map(|crossing| self.hit(crossing))
+    }
+
+    fn intervals(&self, ray: &Ray, intervals: &mut Vec<Interval>) {
+        let local = self.frame.local_ray(ray);
+        if let Some((enter, exit)) = self.boundary(&local).span() {
+            intervals.push(Interval {
+                enter: self.hit(enter),
+                exit: self.hit(exit),
+            });
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.frame.bounds(Aabb::new(
+            Vec3::new(-self.radius, 0., -self.radius),
+            Vec3::new(self.radius, self.height, self.radius),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cone() -> Cone {
+        Cone::new(Vec3::ZERO, Vec3::Y, 1., 1., MaterialId(0))
+    }
+
+    #[test]
+    fn hit_side() {
+        // halfway up the radius has shrunk to one half
+        let ray = Ray::new(Vec3::new(-5., 0.5, 0.), Vec3::X, 0.);
+        let hit = cone().intersect(&ray).unwrap();
+
+        assert!((hit.distance - 4.5).abs() < 1e-5);
+        let expected = Vec3::new(-1., 1., 0.).normalize();
+        assert!((hit.normal - expected).length() < 1e-5);
+    }
+
+    #[test]
+    fn hit_base() {
+        let ray = Ray::new(Vec3::new(0.5, -3., 0.), Vec3::Y, 0.);
+        let hit = cone().intersect(&ray).unwrap();
+
+        assert!((hit.distance - 3.).abs() < 1e-5);
+        assert!((hit.normal - Vec3::NEG_Y).length() < 1e-5);
+    }
+
+    #[test]
+    fn miss() {
+        let above_apex = Ray::new(Vec3::new(-5., 1.5, 0.), Vec3::X, 0.);
+        assert!(cone().intersect(&above_apex).is_none());
+
+        // would hit the mirrored cone above the apex
+        let mirrored = Ray::new(Vec3::new(0.9, 5., 0.), Vec3::NEG_Y, 0.);
+        let hit = cone().intersect(&mirrored);
+        assert!(hit.is_none_or(|hit| hit.distance > 4.), "{hit:?}");
+    }
+
+    #[test]
+    fn inside() {
+        let ray = Ray::new(Vec3::new(0., 0.5, 0.), Vec3::Z, 0.);
+        let hit = cone().intersect(&ray).unwrap();
+
+        assert!((hit.distance - 0.5).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,291 @@
+use crate::{aabb::Aabb, consts::MIN_HIT_DISTANCE, ray::Ray};
+
+use super::{Hit, Intersectable, Interval, Object};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    /// The left solid with the right solid carved out of it.
+    Difference,
+}
+
+impl CsgOperation {
+    fn inside(self, left: bool, right: bool) -> bool {
+        match self {
+            CsgOperation::Union => left || right,
+            CsgOperation::Intersection => left && right,
+            CsgOperation::Difference => left && !right,
+        }
+    }
+}
+
+/// Constructive solid geometry node combining two solids. Both children have to report their
+/// [`intervals`](Intersectable::intervals), which is what the combination operates on; primitive
+/// solids, closed meshes and groups or instances of them do.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Csg {
+    pub operation: CsgOperation,
+    left: Box<Object>,
+    right: Box<Object>,
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, left: Object, right: Object) -> Self {
+        Csg {
+            operation,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    pub fn union(left: Object, right: Object) -> Self {
+        Csg::new(CsgOperation::Union, left, right)
+    }
+
+    pub fn intersection(left: Object, right: Object) -> Self {
+        Csg::new(CsgOperation::Intersection, left, right)
+    }
+
+    pub fn difference(left: Object, right: Object) -> Self {
+        Csg::new(CsgOperation::Difference, left, right)
+    }
+}
+
+impl Intersectable for Csg {
+    fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        let mut intervals = Vec::new();
+        self.intervals(ray, &mut intervals);
+
+        intervals
+            .iter()
+            .flat_map(|interval| [interval.enter, interval.exit])
+            .find(|hit| hit.distance > MIN_HIT_DISTANCE)
+    }
+
+    fn intervals(&self, ray: &Ray, intervals: &mut Vec<Interval>) {
+        let mut left = Vec::new();
+        self.left.intervals(ray, &mut left);
+        let mut right = Vec::new();
+        self.right.intervals(ray, &mut right);
+
+        // (hit, belongs to the right child, enters the child)
+        let mut events = left
+            .iter()
+            .map(|interval| (interval, false))
+            .chain(right.iter().map(|interval| (interval, true)))
+            .flat_map(|(interval, is_right)| {
+                [
+                    (interval.enter, is_right, true),
+                    (interval.exit, is_right, false),
+                ]
+            })
+            .collect::<Vec<_>>();
+        events.sort_by(|a, b| a.0.distance.total_cmp(&b.0.distance));
+
+        let (mut in_left, mut in_right) = (false, false);
+        let mut enter = None;
+        for (hit, is_right, entering) in events {
+            let was_inside = self.operation.inside(in_left, in_right);
+            if is_right {
+                in_right = entering;
+            } else {
+                in_left = entering;
+            }
+            let inside = self.operation.inside(in_left, in_right);
+
+            // the surface of a carved out solid faces into it
+            let hit = if is_right && self.operation == CsgOperation::Difference {
+                Hit {
+                    normal: -hit.normal,
+                    ..hit
+                }
+            } else {
+                hit
+            };
+
+            if !was_inside && inside {
+                enter = Some(hit);
+            } else if was_inside && !inside {
+                if let Some(enter) = enter.take() {
+                    intervals.push(Interval { enter, exit: hit });
+                }
+            }
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        let left = self.left.bounds();
+        let right = self.right.bounds();
+
+        match self.operation {
+            CsgOperation::Union => left.union(right),
+            CsgOperation::Intersection => {
+                Aabb::new(left.min.max(right.min), left.max.min(right.max))
+            }
+            CsgOperation::Difference => left,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use glam::{Affine3A, Vec3};
+
+    use super::*;
+    use crate::{
+        object::{group::Group, instance::Instance, mesh::Mesh, plane::Plane, sphere::Sphere},
+        object_store::MaterialId,
+    };
+
+    fn spheres() -> (Object, Object) {
+        (
+            Sphere::new(Vec3::new(-0.5, 0., 0.), 1., MaterialId(0)).into(),
+            Sphere::new(Vec3::new(0.5, 0., 0.), 1., MaterialId(1)).into(),
+        )
+    }
+
+    fn along_x() -> Ray {
+        Ray::new(Vec3::new(-5., 0., 0.), Vec3::X, 0.)
+    }
+
+    fn spans(csg: &Csg) -> Vec<(f32, f32)> {
+        let mut intervals = Vec::new();
+        csg.intervals(&along_x(), &mut intervals);
+        intervals
+            .iter()
+            .map(|i| (i.enter.distance, i.exit.distance))
+            .collect()
+    }
+
+    fn assert_spans(actual: Vec<(f32, f32)>, expected: &[(f32, f32)]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?}");
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(
+                (a.0 - e.0).abs() < 1e-5 && (a.1 - e.1).abs() < 1e-5,
+                "{actual:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn union() {
+        let (a, b) = spheres();
+        assert_spans(spans(&Csg::union(a, b)), &[(3.5, 6.5)]);
+    }
+
+    #[test]
+    fn intersection() {
+        let (a, b) = spheres();
+        let csg = Csg::intersection(a, b);
+        assert_spans(spans(&csg), &[(4.5, 5.5)]);
+
+        let hit = csg.intersect(&along_x()).unwrap();
+        assert_eq!(hit.material, MaterialId(1));
+        assert!((hit.normal - Vec3::NEG_X).length() < 1e-5);
+    }
+
+    #[test]
+    fn difference() {
+        let (a, b) = spheres();
+        let csg = Csg::difference(a, b);
+        assert_spans(spans(&csg), &[(3.5, 4.5)]);
+
+        // shooting back from inside the carved out part hits the inner wall
+        let ray = Ray::new(Vec3::new(0., 0., 0.), Vec3::NEG_X, 0.);
+        let hit = csg.intersect(&ray).unwrap();
+        assert!((hit.distance - 0.5).abs() < 1e-5);
+        assert!((hit.normal - Vec3::X).length() < 1e-5);
+    }
+
+    /// Triangulated cube from -1 to 1, wound counter-clockwise seen from outside.
+    fn cube() -> Object {
+        let positions = (0..8)
+            .map(|i| Vec3::new((i & 1) as f32, (i >> 1 & 1) as f32, (i >> 2 & 1) as f32) * 2. - 1.)
+            .collect();
+        let quads = [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ];
+        let triangles = quads
+            .iter()
+            .flat_map(|[a, b, c, d]| [[*a, *b, *c], [*a, *c, *d]])
+            .collect();
+
+        Mesh::new(positions, Vec::new(), Vec::new(), triangles, MaterialId(2)).into()
+    }
+
+    #[test]
+    fn meshes_groups_and_instances() {
+        let hole = Sphere::new(Vec3::ZERO, 0.5, MaterialId(0)).into();
+        let csg = Csg::difference(cube(), hole);
+        assert_spans(spans(&csg), &[(4., 4.5), (5.5, 6.)]);
+        let hit = csg.intersect(&along_x()).unwrap();
+        assert_eq!(hit.material, MaterialId(2));
+        assert!((hit.normal - Vec3::NEG_X).length() < 1e-5);
+
+        let (a, b) = spheres();
+        let csg = Csg::intersection(Group::new(vec![a, b]).into(), cube());
+        assert_spans(spans(&csg), &[(4., 6.)]);
+
+        let shifted = Instance::new(Arc::new(cube()), Affine3A::from_translation(Vec3::X));
+        let csg = Csg::union(cube(), shifted.into());
+        assert_spans(spans(&csg), &[(4., 7.)]);
+
+        // the line through the cube behind the origin still counts
+        let inside = Ray::new(Vec3::new(0.5, 0., 0.), Vec3::X, 0.);
+        let mut intervals = Vec::new();
+        Csg::union(
+            cube(),
+            Sphere::new(Vec3::new(5., 0., 0.), 1., MaterialId(0)).into(),
+        )
+        .intervals(&inside, &mut intervals);
+        let spans = intervals
+            .iter()
+            .map(|i| (i.enter.distance, i.exit.distance))
+            .collect();
+        assert_spans(spans, &[(-1.5, 0.5), (3.5, 5.5)]);
+    }
+
+    #[test]
+    fn groups_with_unbounded_members() {
+        // the half-space below y = 0 merged with a sphere, cut down to the unit sphere
+        let group = Group::new(vec![
+            Plane::new(Vec3::ZERO, Vec3::Y, MaterialId(0)).into(),
+            Sphere::new(Vec3::new(0., 1., 0.), 0.5, MaterialId(0)).into(),
+        ]);
+        let csg = Csg::intersection(
+            group.into(),
+            Sphere::new(Vec3::ZERO, 1., MaterialId(1)).into(),
+        );
+
+        let half = 0.75f32.sqrt();
+        let ray = Ray::new(Vec3::new(-5., -0.5, 0.), Vec3::X, 0.);
+        let mut intervals = Vec::new();
+        csg.intervals(&ray, &mut intervals);
+        let spans = intervals
+            .iter()
+            .map(|i| (i.enter.distance, i.exit.distance))
+            .collect();
+        assert_spans(spans, &[(5. - half, 5. + half)]);
+
+        // above the plane, only the small sphere is inside the unit sphere
+        let ray = Ray::new(Vec3::new(-5., 0.75, 0.), Vec3::X, 0.);
+        let hit = csg.intersect(&ray).unwrap();
+        let expected = 5. - (0.25f32 - 0.0625).sqrt();
+        assert!((hit.distance - expected).abs() < 1e-5, "{}", hit.distance);
+    }
+
+    #[test]
+    fn miss() {
+        let (a, b) = spheres();
+        let ray = Ray::new(Vec3::new(-5., 2., 0.), Vec3::X, 0.);
+        assert!(Csg::union(a, b).intersect(&ray).is_none());
+    }
+}
@@ -0,0 +1,154 @@
+use glam::{Quat, Vec2, Vec3};
+
+use crate::{aabb::Aabb, consts::MIN_HIT_DISTANCE, object_store::MaterialId, ray::Ray};
+
+use super::{frame::Frame, Hit, Intersectable, Interval};
+
+/// Box with the given half extents around `center`, optionally rotated. Each face is mapped to the
+/// full `[0, 1]²` texture space.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cuboid {
+    pub half_extents: Vec3,
+    pub material: MaterialId,
+    frame: Frame,
+}
+
+impl Cuboid {
+    /// Axis-aligned box spanning `min` to `max`.
+    pub fn new(min: Vec3, max: Vec3, material: MaterialId) -> Self {
+        Cuboid::oriented((min + max) / 2., (max - min) / 2., Quat::IDENTITY, material)
+    }
+
+    pub fn oriented(
+        center: Vec3,
+        half_extents: Vec3,
+        rotation: Quat,
+        material: MaterialId,
+    ) -> Self {
+        Cuboid {
+            half_extents,
+            material,
+            frame: Frame::new(center, rotation),
+        }
+    }
+}
+
+impl Cuboid {
+    /// Distances at which the line of the local-space ray crosses the box, in ascending order.
+    fn crossings(&self, local: &Ray) -> Option<(f32, f32)> {
+        let inv_direction = local.direction.recip();
+        let t1 = (-self.half_extents - local.origin) * inv_direction;
+        let t2 = (self.half_extents - local.origin) * inv_direction;
+
+        let near = t1.min(t2).max_element();
+        let far = t1.max(t2).min_element();
+        (near <= far).then_some((near, far))
+    }
+
+    fn hit(&self, local: &Ray, distance: f32) -> Hit {
+        let p = local.at(distance) / self.half_extents;
+        let abs = p.abs();
+        let (normal, uv) = if abs.x >= abs.y && abs.x >= abs.z {
+            (Vec3::X * p.x.signum(), Vec2::new(p.z, p.y))
+        } else if abs.y >= abs.z {
+            (Vec3::Y * p.y.signum(), Vec2::new(p.x, p.z))
+        } else {
+            (Vec3::Z * p.z.signum(), Vec2::new(p.x, p.y))
+        };
+
+        Hit {
+            distance,
+            normal: self.frame.world_normal(normal),
+            uv: (uv + 1.) / 2.,
+            material: self.material,
+            color: None,
+        }
+    }
+}
+
+impl Intersectable for Cuboid {
+    fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        let local = self.frame.local_ray(ray);
+
+        let (near, far) = self.crossings(&local)?;
+        if far <= MIN_HIT_DISTANCE {
+            return None;
+        }
+
+        // leave through the far side when starting inside the box
+        let distance = if near > MIN_HIT_DISTANCE { near } else { far };
+        Some(self.hit(&local, distance))
+    }
+
+    fn intervals(&self, ray: &Ray, intervals: &mut Vec<Interval>) {
+        let local = self.frame.local_ray(ray);
+        if let Some((near, far)) = self.crossings(&local) {
+            intervals.push(Interval {
+                enter: self.hit(&local, near),
+                exit: self.hit(&local, far),
+            });
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.frame
+            .bounds(Aabb::new(-self.half_extents, self.half_extents))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use super::*;
+
+    fn unit_box() -> Cuboid {
+        Cuboid::new(Vec3::splat(-1.), Vec3::splat(1.), MaterialId(0))
+    }
+
+    #[test]
+    fn hit() {
+        let ray = Ray::new(Vec3::new(0.5, 0.5, 5.), Vec3::NEG_Z, 0.);
+        let hit = unit_box().intersect(&ray).unwrap();
+
+        assert!((hit.distance - 4.).abs() < 1e-5);
+        assert!((hit.normal - Vec3::Z).length() < 1e-5);
+        assert!((hit.uv - Vec2::new(0.75, 0.75)).length() < 1e-5);
+    }
+
+    #[test]
+    fn miss() {
+        let ray = Ray::new(Vec3::new(1.5, 0., 5.), Vec3::NEG_Z, 0.);
+        assert!(unit_box().intersect(&ray).is_none());
+
+        let behind = Ray::new(Vec3::new(0., 0., 5.), Vec3::Z, 0.);
+        assert!(unit_box().intersect(&behind).is_none());
+    }
+
+    #[test]
+    fn inside() {
+        let ray = Ray::new(Vec3::ZERO, Vec3::X, 0.);
+        let hit = unit_box().intersect(&ray).unwrap();
+
+        assert!((hit.distance - 1.).abs() < 1e-5);
+        assert!((hit.normal - Vec3::X).length() < 1e-5);
+    }
+
+    #[test]
+    fn oriented() {
+        let cuboid = Cuboid::oriented(
+            Vec3::ZERO,
+            Vec3::ONE,
+            Quat::from_rotation_y(FRAC_PI_4),
+            MaterialId(0),
+        );
+
+        // the rotated corner now sticks out along +X
+        let ray = Ray::new(Vec3::new(5., 0., 0.), Vec3::NEG_X, 0.);
+        let hit = cuboid.intersect(&ray).unwrap();
+        assert!((hit.distance - (5. - 2f32.sqrt())).abs() < 1e-4);
+
+        let bounds = cuboid.bounds();
+        assert!((bounds.max.x - 2f32.sqrt()).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,152 @@
+use std::f32::consts::PI;
+
+use glam::{Vec2, Vec3};
+
+use crate::{aabb::Aabb, object_store::MaterialId, ray::Ray, solver::solve_quadratic};
+
+use super::{boundary::Boundary, disk::intersect_cap, frame::Frame, Hit, Intersectable, Interval};
+
+/// Capped cylinder extending `height` along `axis` from the center of its base. Texture
+/// coordinates wrap `u` around the side and run `v` from base to top; the caps use polar
+/// coordinates like [`Disk`](super::disk::Disk).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cylinder {
+    pub radius: f32,
+    pub height: f32,
+    pub material: MaterialId,
+    frame: Frame,
+}
+
+impl Cylinder {
+    pub fn new(base: Vec3, axis: Vec3, radius: f32, height: f32, material: MaterialId) -> Self {
+        Cylinder {
+            radius,
+            height,
+            material,
+            frame: Frame::from_axis(base, axis),
+        }
+    }
+
+    fn boundary(&self, local: &Ray) -> Boundary {
+        let (o, d) = (local.origin.as_dvec3(), local.direction.as_dvec3());
+        let radius = self.radius as f64;
+
+        let mut boundary = Boundary::default();
+
+        let roots = solve_quadratic([
+            o.x * o.x + o.z * o.z - radius * radius,
+            2. * (o.x * d.x + o.z * d.z),
+            d.x * d.x + d.z * d.z,
+        ]);
+        for &t in roots.iter() {
+            let t = t as f32;
+            let p = local.at(t);
+            if (0. ..=self.height).contains(&p.y) {
+                let normal = Vec3::new(p.x, 0., p.z) / self.radius;
+                let uv = Vec2::new(0.5 + p.z.atan2(p.x) / (2. * PI), p.y / self.height);
+                boundary.push(t, normal, uv);
+            }
+        }
+
+        if let Some((t, uv)) = intersect_cap(local, 0., self.radius) {
+            boundary.push(t, Vec3::NEG_Y, uv);
+        }
+        if let Some((t, uv)) = intersect_cap(local, self.height, self.radius) {
+            boundary.push(t, Vec3::Y, uv);
+        }
+
+        boundary
+    }
+
+    fn hit(&self, (distance, normal, uv): (f32, Vec3, Vec2)) -> Hit {
+        Hit {
+            distance,
+            normal: self.frame.world_normal(normal),
+            uv,
+            material: self.material,
+            color: None,
+        }
+    }
+}
+
+impl Intersectable for Cylinder {
+    fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        let local = self.frame.local_ray(ray);
+        self.boundary(&local)
+            .first()
+            .map(|crossing| self.hit(crossing))
+    }
+
+    fn intervals(&self, ray: &Ray, intervals: &mut Vec<Interval>) {
+        let local = self.frame.local_ray(ray);
+        if let Some((enter, exit)) = self.boundary(&local).span() {
+            intervals.push(Interval {
+                enter: self.hit(enter),
+                exit: self.hit(exit),
+            });
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.frame.bounds(Aabb::new(
+            Vec3::new(-self.radius, 0., -self.radius),
+            Vec3::new(self.radius, self.height, self.radius),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cylinder() -> Cylinder {
+        Cylinder::new(Vec3::ZERO, Vec3::Y, 1., 2., MaterialId(0))
+    }
+
+    #[test]
+    fn hit_side() {
+        let ray = Ray::new(Vec3::new(-5., 1., 0.), Vec3::X, 0.);
+        let hit = cylinder().intersect(&ray).unwrap();
+
+        assert!((hit.distance - 4.).abs() < 1e-5);
+        assert!((hit.normal - Vec3::NEG_X).length() < 1e-5);
+        assert!((hit.uv.y - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn hit_cap() {
+        let ray = Ray::new(Vec3::new(0.5, 5., 0.), Vec3::NEG_Y, 0.);
+        let hit = cylinder().intersect(&ray).unwrap();
+
+        assert!((hit.distance - 3.).abs() < 1e-5);
+        assert!((hit.normal - Vec3::Y).length() < 1e-5);
+    }
+
+    #[test]
+    fn miss() {
+        let above = Ray::new(Vec3::new(-5., 2.5, 0.), Vec3::X, 0.);
+        assert!(cylinder().intersect(&above).is_none());
+
+        let beside = Ray::new(Vec3::new(1.5, 5., 0.), Vec3::NEG_Y, 0.);
+        assert!(cylinder().intersect(&beside).is_none());
+    }
+
+    #[test]
+    fn inside() {
+        let ray = Ray::new(Vec3::new(0., 1., 0.), Vec3::Z, 0.);
+        let hit = cylinder().intersect(&ray).unwrap();
+
+        assert!((hit.distance - 1.).abs() < 1e-5);
+        assert!((hit.normal - Vec3::Z).length() < 1e-5);
+    }
+
+    #[test]
+    fn tilted_axis() {
+        let cylinder = Cylinder::new(Vec3::ZERO, Vec3::X, 1., 2., MaterialId(0));
+        let ray = Ray::new(Vec3::new(1., 5., 0.), Vec3::NEG_Y, 0.);
+        let hit = cylinder.intersect(&ray).unwrap();
+
+        assert!((hit.distance - 4.).abs() < 1e-5);
+        assert!((hit.normal - Vec3::Y).length() < 1e-5);
+    }
+}
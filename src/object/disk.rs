@@ -0,0 +1,113 @@
+use std::f32::consts::PI;
+
+use glam::{Vec2, Vec3};
+
+use crate::{aabb::Aabb, consts::MIN_HIT_DISTANCE, object_store::MaterialId, ray::Ray};
+
+use super::{frame::Frame, Hit, Intersectable};
+
+/// Flat circular disk facing along `normal`. Texture coordinates are polar: `u` is the angle
+/// around the center and `v` the distance from it, both normalized to `[0, 1]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Disk {
+    pub center: Vec3,
+    pub normal: Vec3,
+    pub radius: f32,
+    pub material: MaterialId,
+    frame: Frame,
+}
+
+impl Disk {
+    pub fn new(center: Vec3, normal: Vec3, radius: f32, material: MaterialId) -> Self {
+        Disk {
+            center,
+            normal: normal.normalize(),
+            radius,
+            material,
+            frame: Frame::from_axis(center, normal),
+        }
+    }
+}
+
+impl Intersectable for Disk {
+    fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        let local = self.frame.local_ray(ray);
+        let (distance, uv) = intersect_cap(&local, 0., self.radius)?;
+        if distance <= MIN_HIT_DISTANCE {
+            return None;
+        }
+
+        Some(Hit {
+            distance,
+            normal: self.normal,
+            uv,
+            material: self.material,
+            color: None,
+        })
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.frame.bounds(Aabb::new(
+            Vec3::new(-self.radius, 0., -self.radius),
+            Vec3::new(self.radius, 0., self.radius),
+        ))
+    }
+}
+
+/// Intersects the line of a local-space ray with the disk of `radius` around the Y axis at
+/// `height`, returning the distance and polar texture coordinates of the hit. The distance may be
+/// negative.
+pub(super) fn intersect_cap(ray: &Ray, height: f32, radius: f32) -> Option<(f32, Vec2)> {
+    if ray.direction.y.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let distance = (height - ray.origin.y) / ray.direction.y;
+
+    let p = ray.at(distance);
+    let r = (p.x * p.x + p.z * p.z).sqrt();
+    if r > radius {
+        return None;
+    }
+
+    Some((
+        distance,
+        Vec2::new(0.5 + p.z.atan2(p.x) / (2. * PI), r / radius),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn disk() -> Disk {
+        Disk::new(Vec3::new(0., 0., -5.), Vec3::Z, 1., MaterialId(0))
+    }
+
+    #[test]
+    fn hit() {
+        let ray = Ray::new(Vec3::new(0.5, 0., 0.), Vec3::NEG_Z, 0.);
+        let hit = disk().intersect(&ray).unwrap();
+
+        assert!((hit.distance - 5.).abs() < 1e-5);
+        assert!((hit.normal - Vec3::Z).length() < 1e-5);
+        assert!((hit.uv.y - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn miss() {
+        let outside = Ray::new(Vec3::new(1.5, 0., 0.), Vec3::NEG_Z, 0.);
+        assert!(disk().intersect(&outside).is_none());
+
+        let behind = Ray::new(Vec3::ZERO, Vec3::Z, 0.);
+        assert!(disk().intersect(&behind).is_none());
+    }
+
+    #[test]
+    fn bounds_are_flat() {
+        let bounds = disk().bounds();
+
+        assert!((bounds.min.z + 5.).abs() < 1e-5 && (bounds.max.z + 5.).abs() < 1e-5);
+        assert!((bounds.max.x - 1.).abs() < 1e-5);
+    }
+}
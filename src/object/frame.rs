@@ -0,0 +1,46 @@
+use glam::{Affine3A, Quat, Vec3};
+
+use crate::{aabb::Aabb, ray::Ray};
+
+/// Rigid placement of a primitive that is defined around the origin with its axis along +Y.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Frame {
+    origin: Vec3,
+    rotation: Quat,
+    inverse_rotation: Quat,
+}
+
+impl Frame {
+    pub fn new(origin: Vec3, rotation: Quat) -> Self {
+        Frame {
+            origin,
+            rotation,
+            inverse_rotation: rotation.inverse(),
+        }
+    }
+
+    /// Frame whose local +Y axis points along `axis`.
+    pub fn from_axis(origin: Vec3, axis: Vec3) -> Self {
+        Frame::new(origin, Quat::from_rotation_arc(Vec3::Y, axis.normalize()))
+    }
+
+    /// Moves `ray` into local space. Distances along the ray are preserved.
+    pub fn local_ray(&self, ray: &Ray) -> Ray {
+        Ray::new(
+            self.inverse_rotation * (ray.origin - self.origin),
+            self.inverse_rotation * ray.direction,
+            ray.time,
+        )
+    }
+
+    pub fn world_normal(&self, normal: Vec3) -> Vec3 {
+        self.rotation * normal
+    }
+
+    pub fn bounds(&self, local: Aabb) -> Aabb {
+        local.transform(&Affine3A::from_rotation_translation(
+            self.rotation,
+            self.origin,
+        ))
+    }
+}
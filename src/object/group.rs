@@ -0,0 +1,62 @@
+use crate::{aabb::Aabb, bvh::Bvh, ray::Ray};
+
+use super::{line_behind, Hit, Intersectable, Interval, Object};
+
+/// Collection of objects intersected through a bounding volume hierarchy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    objects: Vec<Object>,
+    bvh: Bvh,
+}
+
+impl Group {
+    pub fn new(mut objects: Vec<Object>) -> Self {
+        let bvh = Bvh::build(&mut objects, Intersectable::bounds);
+        Group { objects, bvh }
+    }
+}
+
+impl Intersectable for Group {
+    fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        self.bvh
+            .closest_hit(ray, |i| {
+                let hit = self.objects[i].intersect(ray)?;
+                Some((hit.distance, hit))
+            })
+            .map(|(_, hit)| hit)
+    }
+
+    /// The union of the intervals of the members.
+    fn intervals(&self, ray: &Ray, intervals: &mut Vec<Interval>) {
+        let mut members = Vec::new();
+        let bounds = self.bounds();
+        if bounds.is_bounded() {
+            let (line, _) = line_behind(bounds, ray);
+            self.bvh
+                .for_each_candidate(&line, |i| self.objects[i].intervals(ray, &mut members));
+        } else {
+            // unbounded members such as planes can't be skipped by the hierarchy
+            for object in &self.objects {
+                object.intervals(ray, &mut members);
+            }
+        }
+        members.sort_by(|a, b| a.enter.distance.total_cmp(&b.enter.distance));
+
+        let mut merged: Option<Interval> = None;
+        for interval in members {
+            match &mut merged {
+                Some(current) if interval.enter.distance <= current.exit.distance => {
+                    if interval.exit.distance > current.exit.distance {
+                        current.exit = interval.exit;
+                    }
+                }
+                _ => intervals.extend(merged.replace(interval)),
+            }
+        }
+        intervals.extend(merged);
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.bvh.bounds()
+    }
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use glam::Affine3A;
+
+use crate::{aabb::Aabb, ray::Ray};
+
+use super::{Hit, Intersectable, Interval, Object};
+
+/// Places shared geometry in the scene with an affine object-to-world transform. The geometry
+/// itself is never copied, so a mesh or group can be instanced any number of times.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instance {
+    geometry: Arc<Object>,
+    transform: Affine3A,
+    inverse: Affine3A,
+}
+
+impl Instance {
+    pub fn new(geometry: Arc<Object>, transform: Affine3A) -> Self {
+        Instance {
+            geometry,
+            transform,
+            inverse: transform.inverse(),
+        }
+    }
+
+    pub fn geometry(&self) -> &Arc<Object> {
+        &self.geometry
+    }
+
+    pub fn transform(&self) -> Affine3A {
+        self.transform
+    }
+}
+
+impl Intersectable for Instance {
+    fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        intersect_transformed(&self.geometry, &self.inverse, ray)
+    }
+
+    fn intervals(&self, ray: &Ray, intervals: &mut Vec<Interval>) {
+        intervals_transformed(&self.geometry, &self.inverse, ray, intervals);
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.geometry.bounds().transform(&self.transform)
+    }
+}
+
+/// Intersects `object` with `ray` after moving the ray into object space with the world-to-object
+/// transform `inverse`. The returned distance and normal are in world space; normals are
+/// transformed by the inverse transpose so that they stay perpendicular under non-uniform scaling.
+pub(super) fn intersect_transformed(object: &Object, inverse: &Affine3A, ray: &Ray) -> Option<Hit> {
+    let (local, scale) = object_space(inverse, ray);
+    let hit = object.intersect(&local)?;
+    Some(world_hit(hit, inverse, scale))
+}
+
+/// Like [`intersect_transformed`], but for the intervals of a solid.
+pub(super) fn intervals_transformed(
+    object: &Object,
+    inverse: &Affine3A,
+    ray: &Ray,
+    intervals: &mut Vec<Interval>,
+) {
+    let (local, scale) = object_space(inverse, ray);
+
+    let start = intervals.len();
+    object.intervals(&local, intervals);
+    for interval in &mut intervals[start..] {
+        interval.enter = world_hit(interval.enter, inverse, scale);
+        interval.exit = world_hit(interval.exit, inverse, scale);
+    }
+}
+
+/// Returns the object-space ray with a normalized direction, and the factor by which distances
+/// along it are longer than in world space.
+fn object_space(inverse: &Affine3A, ray: &Ray) -> (Ray, f32) {
+    let direction = inverse.transform_vector3(ray.direction);
+    let scale = direction.length();
+    let local = Ray::new(
+        inverse.transform_point3(ray.origin),
+        direction / scale,
+        ray.time,
+    );
+    (local, scale)
+}
+
+fn world_hit(hit: Hit, inverse: &Affine3A, scale: f32) -> Hit {
+    Hit {
+        distance: hit.distance / scale,
+        normal: (inverse.matrix3.transpose() * hit.normal).normalize_or_zero(),
+        ..hit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::*;
+    use crate::{object::sphere::Sphere, object_store::MaterialId};
+
+    #[test]
+    fn non_uniform_scale_keeps_normals_perpendicular() {
+        // a unit sphere stretched into an ellipsoid twice as wide along x
+        let sphere = Arc::new(Sphere::new(Vec3::ZERO, 1., MaterialId(0)).into());
+        let ellipsoid = Instance::new(sphere, Affine3A::from_scale(Vec3::new(2., 1., 1.)));
+
+        let side = Ray::new(Vec3::new(5., 0., 0.), Vec3::NEG_X, 0.);
+        let hit = ellipsoid.intersect(&side).unwrap();
+        assert!((hit.distance - 3.).abs() < 1e-5);
+        assert!(hit.normal.abs_diff_eq(Vec3::X, 1e-5));
+
+        // above the point (√2, √½, 0), where the surface slopes with a normal along (1, 2, 0)
+        let x = 2f32.sqrt();
+        let top = Ray::new(Vec3::new(x, 5., 0.), Vec3::NEG_Y, 0.);
+        let hit = ellipsoid.intersect(&top).unwrap();
+        assert!((hit.distance - (5. - 0.5f32.sqrt())).abs() < 1e-5);
+        assert!(hit
+            .normal
+            .abs_diff_eq(Vec3::new(1., 2., 0.).normalize(), 1e-5));
+        assert!(ellipsoid
+            .bounds()
+            .max
+            .abs_diff_eq(Vec3::new(2., 1., 1.), 1e-5));
+    }
+}
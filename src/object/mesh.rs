@@ -0,0 +1,265 @@
+use glam::{Vec2, Vec3};
+
+use crate::{
+    aabb::Aabb, bvh::Bvh, color::Color, object_store::MaterialId, ray::Ray,
+    stats::count_triangle_tests,
+};
+
+use super::{line_behind, triangle::intersect_triangle, Hit, Intersectable, Interval};
+
+/// Indexed triangle mesh. Vertex attributes are stored once and shared by all faces; normals and
+/// texture coordinates and colors are optional and, when present, indexed like the positions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mesh {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<Vec2>,
+    colors: Vec<Color>,
+    triangles: Vec<[u32; 3]>,
+    material: MaterialId,
+    bvh: Bvh,
+}
+
+impl Mesh {
+    pub fn new(
+        positions: Vec<Vec3>,
+        normals: Vec<Vec3>,
+        uvs: Vec<Vec2>,
+        mut triangles: Vec<[u32; 3]>,
+        material: MaterialId,
+    ) -> Self {
+        let bvh = Bvh::build(&mut triangles, |triangle| {
+            Aabb::from_points(triangle.map(|i| positions[i as usize]))
+        });
+
+        Mesh {
+            positions,
+            normals,
+            uvs,
+            colors: Vec::new(),
+            triangles,
+            material,
+            bvh,
+        }
+    }
+
+    /// Sets per-vertex colors, which replace the diffuse color of the mesh's material.
+    pub fn with_colors(mut self, colors: Vec<Color>) -> Self {
+        assert_eq!(colors.len(), self.positions.len());
+        self.colors = colors;
+        self
+    }
+
+    pub fn positions(&self) -> &[Vec3] {
+        &self.positions
+    }
+
+    pub fn triangles(&self) -> &[[u32; 3]] {
+        &self.triangles
+    }
+
+    pub fn material(&self) -> MaterialId {
+        self.material
+    }
+
+    fn hit(&self, triangle: [u32; 3], distance: f32, u: f32, v: f32) -> Hit {
+        let [i0, i1, i2] = triangle.map(|i| i as usize);
+        let w = 1. - u - v;
+
+        let normal = if self.normals.is_empty() {
+            let origin = self.positions[i0];
+            (self.positions[i1] - origin)
+                .cross(self.positions[i2] - origin)
+                .normalize()
+        } else {
+            (self.normals[i0] * w + self.normals[i1] * u + self.normals[i2] * v).normalize()
+        };
+
+        let uv = if self.uvs.is_empty() {
+            Vec2::new(u, v)
+        } else {
+            self.uvs[i0] * w + self.uvs[i1] * u + self.uvs[i2] * v
+        };
+
+        let color = (!self.colors.is_empty())
+            .then(|| self.colors[i0] * w + self.colors[i1] * u + self.colors[i2] * v);
+
+        Hit {
+            distance,
+            normal,
+            uv,
+            material: self.material,
+            color,
+        }
+    }
+}
+
+impl Intersectable for Mesh {
+    fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        let mut tests = 0;
+        let closest = self.bvh.closest_hit(ray, |i| {
+            tests += 1;
+            let triangle = self.triangles[i];
+            let [a, b, c] = triangle.map(|i| self.positions[i as usize]);
+            let (t, u, v) = intersect_triangle(a, b - a, c - a, ray)?;
+            Some((t, (triangle, u, v)))
+        });
+        count_triangle_tests(tests);
+
+        closest.map(|(t, (triangle, u, v))| self.hit(triangle, t, u, v))
+    }
+
+    fn intervals(&self, ray: &Ray, intervals: &mut Vec<Interval>) {
+        let (line, back) = line_behind(self.bounds(), ray);
+
+        // (hit, enters the mesh)
+        let mut crossings = Vec::new();
+        let mut tests = 0;
+        self.bvh.for_each_candidate(&line, |i| {
+            tests += 1;
+            let triangle = self.triangles[i];
+            let [a, b, c] = triangle.map(|i| self.positions[i as usize]);
+            if let Some((t, u, v)) = intersect_triangle(a, b - a, c - a, &line) {
+                let entering = (b - a).cross(c - a).dot(ray.direction) < 0.;
+                crossings.push((self.hit(triangle, t - back, u, v), entering));
+            }
+        });
+        count_triangle_tests(tests);
+        crossings.sort_by(|a, b| a.0.distance.total_cmp(&b.0.distance));
+
+        // crossings alternate between entering and leaving, except where the line passes through
+        // an edge or vertex shared by several faces and crosses each of them
+        let mut enter = None;
+        for (hit, entering) in crossings {
+            match enter {
+                None if entering => enter = Some(hit),
+                Some(start) if !entering => {
+                    intervals.push(Interval {
+                        enter: start,
+                        exit: hit,
+                    });
+                    enter = None;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.bvh.bounds()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::vec3;
+
+    use super::*;
+
+    /// Unit square in the z = 0 plane facing +z, split along its diagonal from (0, 0) to (1, 1),
+    /// with normals tilted outwards at the corners and texture coordinates spanning [0, 2].
+    fn square(material: MaterialId) -> Mesh {
+        let positions = vec![
+            vec3(0., 0., 0.),
+            vec3(1., 0., 0.),
+            vec3(1., 1., 0.),
+            vec3(0., 1., 0.),
+        ];
+        let normals = positions
+            .iter()
+            .map(|p| (*p - vec3(0.5, 0.5, -1.)).normalize())
+            .collect();
+        let uvs = positions.iter().map(|p| p.truncate() * 2.).collect();
+
+        Mesh::new(
+            positions,
+            normals,
+            uvs,
+            vec![[0, 1, 2], [0, 2, 3]],
+            material,
+        )
+    }
+
+    fn down_at(x: f32, y: f32) -> Ray {
+        Ray::new(vec3(x, y, 2.), Vec3::NEG_Z, 0.)
+    }
+
+    #[test]
+    fn interpolates_normals_and_texture_coordinates() {
+        let mesh = square(MaterialId(0));
+
+        // (0.75, 0.25) lies in the first triangle with the barycentric weights ¼, ½ and ¼
+        let hit = mesh.intersect(&down_at(0.75, 0.25)).unwrap();
+        assert!((hit.distance - 2.).abs() < 1e-6);
+
+        let n = &mesh.normals;
+        let expected = (n[0] * 0.25 + n[1] * 0.5 + n[2] * 0.25).normalize();
+        assert!(hit.normal.abs_diff_eq(expected, 1e-5), "{}", hit.normal);
+        assert!(hit.uv.abs_diff_eq(Vec2::new(1.5, 0.5), 1e-5), "{}", hit.uv);
+
+        // halfway between opposite corners, their tilts cancel out
+        let hit = mesh.intersect(&down_at(0.5, 0.5)).unwrap();
+        assert!(hit.normal.abs_diff_eq(Vec3::Z, 1e-5), "{}", hit.normal);
+        assert!(hit.uv.abs_diff_eq(Vec2::ONE, 1e-5), "{}", hit.uv);
+    }
+
+    #[test]
+    fn every_face_reports_the_mesh_material() {
+        let red = square(MaterialId(3));
+        let blue = Mesh::new(
+            red.positions.clone(),
+            Vec::new(),
+            Vec::new(),
+            red.triangles.clone(),
+            MaterialId(7),
+        );
+
+        for (x, y) in [(0.75, 0.25), (0.25, 0.75)] {
+            assert_eq!(
+                red.intersect(&down_at(x, y)).unwrap().material,
+                MaterialId(3)
+            );
+            assert_eq!(
+                blue.intersect(&down_at(x, y)).unwrap().material,
+                MaterialId(7)
+            );
+        }
+        // without normals, the geometric normal of the face is used
+        let hit = blue.intersect(&down_at(0.25, 0.75)).unwrap();
+        assert!(hit.normal.abs_diff_eq(Vec3::Z, 1e-6));
+    }
+
+    #[test]
+    fn shared_edges_are_crossed_once() {
+        // the diagonal is shared by both triangles of the square
+        let mesh = square(MaterialId(0));
+        let hit = mesh.intersect(&down_at(0.3, 0.3)).unwrap();
+        assert!((hit.distance - 2.).abs() < 1e-6);
+
+        // a cube from -1 to 1 whose faces are split along diagonals through their centers, so
+        // that a ray along x passes through a shared edge on the way in and out
+        let positions = (0..8)
+            .map(|i| vec3((i & 1) as f32, (i >> 1 & 1) as f32, (i >> 2 & 1) as f32) * 2. - 1.)
+            .collect();
+        let quads = [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ];
+        let triangles = quads
+            .iter()
+            .flat_map(|[a, b, c, d]| [[*a, *b, *c], [*a, *c, *d]])
+            .collect();
+        let cube = Mesh::new(positions, Vec::new(), Vec::new(), triangles, MaterialId(0));
+
+        let ray = Ray::new(vec3(-5., 0., 0.), Vec3::X, 0.);
+        let mut intervals = Vec::new();
+        cube.intervals(&ray, &mut intervals);
+        assert_eq!(intervals.len(), 1);
+        assert!((intervals[0].enter.distance - 4.).abs() < 1e-5);
+        assert!((intervals[0].exit.distance - 6.).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,178 @@
+use glam::{Affine3A, Quat, Vec3};
+
+use crate::{aabb::Aabb, ray::Ray};
+
+use super::{
+    instance::{intersect_transformed, intervals_transformed},
+    Hit, Intersectable, Interval, Object,
+};
+
+/// Wraps an object with a transform that is interpolated between `start` and `end` over the time
+/// interval `[start_time, end_time]`, producing motion blur when rays are sampled over a shutter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Moving {
+    object: Box<Object>,
+    start: (Vec3, Quat, Vec3),
+    end: (Vec3, Quat, Vec3),
+    start_time: f32,
+    end_time: f32,
+}
+
+impl Moving {
+    /// Both transforms are decomposed into scale, rotation and translation, which are
+    /// interpolated separately. Shear can't be expressed that way and is lost, so transforms must
+    /// not contain any.
+    pub fn new(
+        object: Object,
+        start: Affine3A,
+        end: Affine3A,
+        start_time: f32,
+        end_time: f32,
+    ) -> Self {
+        Moving {
+            object: Box::new(object),
+            start: start.to_scale_rotation_translation(),
+            end: end.to_scale_rotation_translation(),
+            start_time,
+            end_time,
+        }
+    }
+
+    pub fn set_motion(&mut self, start: Affine3A, end: Affine3A, start_time: f32, end_time: f32) {
+        self.start = start.to_scale_rotation_translation();
+        self.end = end.to_scale_rotation_translation();
+        self.start_time = start_time;
+        self.end_time = end_time;
+    }
+
+    /// Returns the object-to-world transform at `time`, clamped to the motion interval.
+    pub fn transform_at(&self, time: f32) -> Affine3A {
+        let duration = self.end_time - self.start_time;
+        let t = if duration > 0. {
+            ((time - self.start_time) / duration).clamp(0., 1.)
+        } else {
+            0.
+        };
+
+        let (start_scale, start_rotation, start_translation) = self.start;
+        let (end_scale, end_rotation, end_translation) = self.end;
+
+        Affine3A::from_scale_rotation_translation(
+            start_scale.lerp(end_scale, t),
+            start_rotation.slerp(end_rotation, t),
+            start_translation.lerp(end_translation, t),
+        )
+    }
+}
+
+impl Intersectable for Moving {
+    fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        let inverse = self.transform_at(ray.time).inverse();
+        intersect_transformed(&self.object, &inverse, ray)
+    }
+
+    fn intervals(&self, ray: &Ray, intervals: &mut Vec<Interval>) {
+        let inverse = self.transform_at(ray.time).inverse();
+        intervals_transformed(&self.object, &inverse, ray, intervals);
+    }
+
+    /// Bounds of the whole sweep. Without a change of rotation, every point moves along a straight
+    /// line between its ends. Rotating points stay within the reach of the farthest corner from
+    /// the object's origin, at the larger of both scales, around the moving translation.
+    fn bounds(&self) -> Aabb {
+        let bounds = self.object.bounds();
+        let (start_scale, start_rotation, start_translation) = self.start;
+        let (end_scale, end_rotation, end_translation) = self.end;
+
+        if start_rotation == end_rotation || start_rotation == -end_rotation {
+            return bounds
+                .transform(&self.transform_at(self.start_time))
+                .union(bounds.transform(&self.transform_at(self.end_time)));
+        }
+
+        let scale = start_scale.abs().max(end_scale.abs());
+        let reach = bounds
+            .corners()
+            .iter()
+            .map(|corner| (*corner * scale).length())
+            .fold(0., f32::max);
+        Aabb::from_points([start_translation, end_translation])
+            .union(Aabb::new(
+                start_translation - Vec3::splat(reach),
+                start_translation + Vec3::splat(reach),
+            ))
+            .union(Aabb::new(
+                end_translation - Vec3::splat(reach),
+                end_translation + Vec3::splat(reach),
+            ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+    use crate::{object::sphere::Sphere, object_store::MaterialId};
+
+    fn sphere() -> Object {
+        Sphere::new(Vec3::new(2., 0., 0.), 0.5, MaterialId(0)).into()
+    }
+
+    #[test]
+    fn interpolates_the_position_over_the_shutter() {
+        let moving = Moving::new(
+            sphere(),
+            Affine3A::IDENTITY,
+            Affine3A::from_translation(Vec3::new(0., 4., 0.)),
+            0.,
+            1.,
+        );
+
+        let ray = |time| Ray::new(Vec3::new(2., 2., 5.), Vec3::NEG_Z, time);
+        assert!(moving.intersect(&ray(0.)).is_none());
+        let hit = moving.intersect(&ray(0.5)).unwrap();
+        assert!((hit.distance - 4.5).abs() < 1e-5);
+        assert!(moving.intersect(&ray(1.)).is_none());
+        // outside the motion the object rests at the ends
+        assert!(moving.intersect(&ray(-1.)).is_none());
+    }
+
+    #[test]
+    fn bounds_enclose_the_rotation() {
+        // swings the sphere around the y axis from 45° to 223°, through 90° where it reaches
+        // further along z than at either end or the midpoint
+        let moving = Moving::new(
+            sphere(),
+            Affine3A::from_rotation_y(PI / 4.),
+            Affine3A::from_rotation_y(PI / 4. + PI * 178. / 180.),
+            0.,
+            1.,
+        );
+        let bounds = moving.bounds();
+
+        for i in 0..=20 {
+            let time = i as f32 / 20.;
+            let center = moving
+                .transform_at(time)
+                .transform_point3(Vec3::new(2., 0., 0.));
+            // shoot at the sphere from outside the bounds along each axis
+            for axis in [
+                Vec3::X,
+                Vec3::Y,
+                Vec3::Z,
+                Vec3::NEG_X,
+                Vec3::NEG_Y,
+                Vec3::NEG_Z,
+            ] {
+                let ray = Ray::new(center + axis * 10., -axis, time);
+                let hit = moving.intersect(&ray).unwrap();
+                let point = ray.at(hit.distance);
+                assert!(
+                    point.cmpge(bounds.min - 1e-4).all() && point.cmple(bounds.max + 1e-4).all(),
+                    "{point} at {time} outside {bounds:?}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use glam::{Vec2, Vec3};
+
+use crate::{
+    aabb::Aabb,
+    consts::{FAR_AWAY, MIN_HIT_DISTANCE},
+    object_store::MaterialId,
+    ray::Ray,
+};
+
+use super::{Hit, Intersectable, Interval};
+
+/// Infinite plane through `point`. Texture coordinates are world-space distances along two
+/// tangents of the plane, so textures tile across it. As a solid, the plane bounds the half-space
+/// behind its normal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plane {
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub material: MaterialId,
+}
+
+impl Plane {
+    pub fn new(point: Vec3, normal: Vec3, material: MaterialId) -> Self {
+        Plane {
+            point,
+            normal: normal.normalize(),
+            material,
+        }
+    }
+
+    fn hit(&self, ray: &Ray, distance: f32) -> Hit {
+        let (tangent, bitangent) = self.normal.any_orthonormal_pair();
+        let offset = ray.at(distance) - self.point;
+
+        Hit {
+            distance,
+            normal: self.normal,
+            uv: Vec2::new(offset.dot(tangent), offset.dot(bitangent)),
+            material: self.material,
+            color: None,
+        }
+    }
+
+    fn infinity(&self, distance: f32) -> Hit {
+        Hit {
+            distance,
+            normal: Vec3::ZERO,
+            uv: Vec2::ZERO,
+            material: self.material,
+            color: None,
+        }
+    }
+}
+
+impl Intersectable for Plane {
+    fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        let denom = self.normal.dot(ray.direction);
+        if denom.abs() < f32::EPSILON {
+            return None; // ray is parallel to the plane
+        }
+
+        let distance = (self.point - ray.origin).dot(self.normal) / denom;
+        if distance <= MIN_HIT_DISTANCE {
+            return None;
+        }
+
+        Some(self.hit(ray, distance))
+    }
+
+    fn intervals(&self, ray: &Ray, intervals: &mut Vec<Interval>) {
+        let height = (ray.origin - self.point).dot(self.normal);
+        let denom = self.normal.dot(ray.direction);
+
+        let interval = if denom.abs() < f32::EPSILON {
+            if height > 0. {
+                return;
+            }
+            Interval {
+                enter: self.infinity(f32::NEG_INFINITY),
+                exit: self.infinity(f32::INFINITY),
+            }
+        } else if denom > 0. {
+            Interval {
+                enter: self.infinity(f32::NEG_INFINITY),
+                exit: self.hit(ray, -height / denom),
+            }
+        } else {
+            Interval {
+                enter: self.hit(ray, -height / denom),
+                exit: self.infinity(f32::INFINITY),
+            }
+        };
+
+        intervals.push(interval);
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::new(Vec3::splat(-FAR_AWAY), Vec3::splat(FAR_AWAY))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn floor() -> Plane {
+        Plane::new(Vec3::ZERO, Vec3::Y, MaterialId(0))
+    }
+
+    #[test]
+    fn hit() {
+        let ray = Ray::new(Vec3::new(1., 2., 3.), Vec3::NEG_Y, 0.);
+        let hit = floor().intersect(&ray).unwrap();
+
+        assert!((hit.distance - 2.).abs() < 1e-5);
+        assert_eq!(hit.normal, Vec3::Y);
+    }
+
+    #[test]
+    fn miss() {
+        let away = Ray::new(Vec3::new(0., 2., 0.), Vec3::Y, 0.);
+        assert!(floor().intersect(&away).is_none());
+
+        let parallel = Ray::new(Vec3::new(0., 2., 0.), Vec3::X, 0.);
+        assert!(floor().intersect(&parallel).is_none());
+    }
+
+    #[test]
+    fn from_below() {
+        let ray = Ray::new(Vec3::new(0., -1., 0.), Vec3::Y, 0.);
+        let hit = floor().intersect(&ray).unwrap();
+
+        assert!((hit.distance - 1.).abs() < 1e-5);
+    }
+}
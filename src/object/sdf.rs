@@ -0,0 +1,380 @@
+use glam::{Vec2, Vec3};
+
+use crate::{
+    aabb::Aabb,
+    consts::{FAR_AWAY, MIN_HIT_DISTANCE},
+    object_store::MaterialId,
+    ray::Ray,
+};
+
+use super::{Hit, Intersectable};
+
+const MAX_STEPS: u32 = 256;
+const HIT_DISTANCE: f32 = 1e-5;
+const GRADIENT_STEP: f32 = 1e-4;
+
+/// Composable signed distance function: negative inside the described solid, positive outside.
+/// Primitives are centered on the origin; use [`Sdf::translate`] to place them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sdf {
+    Sphere {
+        radius: f32,
+    },
+    /// Box with the given half extents and its edges rounded off by `rounding`.
+    Cuboid {
+        half_extents: Vec3,
+        rounding: f32,
+    },
+    /// Torus around the Y axis.
+    Torus {
+        major_radius: f32,
+        minor_radius: f32,
+    },
+    Capsule {
+        a: Vec3,
+        b: Vec3,
+        radius: f32,
+    },
+    Translate(Vec3, Box<Sdf>),
+    Union(Box<Sdf>, Box<Sdf>),
+    Intersection(Box<Sdf>, Box<Sdf>),
+    Subtraction(Box<Sdf>, Box<Sdf>),
+    /// Union blending the two surfaces together over a distance of roughly `k`.
+    SmoothUnion(f32, Box<Sdf>, Box<Sdf>),
+    /// Infinite repetition with the given cell size; axes with a period of zero aren't repeated.
+    Repeat(Vec3, Box<Sdf>),
+    /// Sinusoidal surface displacement of the given amplitude and spatial frequency.
+    Displace {
+        amplitude: f32,
+        frequency: f32,
+        sdf: Box<Sdf>,
+    },
+}
+
+impl Sdf {
+    pub fn sphere(radius: f32) -> Self {
+        Sdf::Sphere { radius }
+    }
+
+    pub fn cuboid(half_extents: Vec3, rounding: f32) -> Self {
+        Sdf::Cuboid {
+            half_extents,
+            rounding,
+        }
+    }
+
+    pub fn torus(major_radius: f32, minor_radius: f32) -> Self {
+        Sdf::Torus {
+            major_radius,
+            minor_radius,
+        }
+    }
+
+    pub fn capsule(a: Vec3, b: Vec3, radius: f32) -> Self {
+        Sdf::Capsule { a, b, radius }
+    }
+
+    pub fn translate(self, offset: Vec3) -> Self {
+        Sdf::Translate(offset, Box::new(self))
+    }
+
+    pub fn union(self, other: Sdf) -> Self {
+        Sdf::Union(Box::new(self), Box::new(other))
+    }
+
+    pub fn intersection(self, other: Sdf) -> Self {
+        Sdf::Intersection(Box::new(self), Box::new(other))
+    }
+
+    pub fn subtract(self, other: Sdf) -> Self {
+        Sdf::Subtraction(Box::new(self), Box::new(other))
+    }
+
+    pub fn smooth_union(self, other: Sdf, k: f32) -> Self {
+        Sdf::SmoothUnion(k, Box::new(self), Box::new(other))
+    }
+
+    pub fn repeat(self, period: Vec3) -> Self {
+        Sdf::Repeat(period, Box::new(self))
+    }
+
+    pub fn displace(self, amplitude: f32, frequency: f32) -> Self {
+        Sdf::Displace {
+            amplitude,
+            frequency,
+            sdf: Box::new(self),
+        }
+    }
+
+    pub fn distance(&self, p: Vec3) -> f32 {
+        match self {
+            Sdf::Sphere { radius } => p.length() - radius,
+            Sdf::Cuboid {
+                half_extents,
+                rounding,
+            } => {
+                let q = p.abs() - *half_extents;
+                q.max(Vec3::ZERO).length() + q.max_element().min(0.) - rounding
+            }
+            Sdf::Torus {
+                major_radius,
+                minor_radius,
+            } => {
+                let ring = Vec2::new(p.x, p.z).length() - major_radius;
+                Vec2::new(ring, p.y).length() - minor_radius
+            }
+            Sdf::Capsule { a, b, radius } => {
+                let pa = p - *a;
+                let ba = *b - *a;
+                let h = (pa.dot(ba) / ba.length_squared()).clamp(0., 1.);
+                (pa - ba * h).length() - radius
+            }
+            Sdf::Translate(offset, sdf) => sdf.distance(p - *offset),
+            Sdf::Union(a, b) => a.distance(p).min(b.distance(p)),
+            Sdf::Intersection(a, b) => a.distance(p).max(b.distance(p)),
+            Sdf::Subtraction(a, b) => a.distance(p).max(-b.distance(p)),
+            Sdf::SmoothUnion(k, a, b) => {
+                let (d1, d2) = (a.distance(p), b.distance(p));
+                let h = (0.5 + 0.5 * (d2 - d1) / k).clamp(0., 1.);
+                d2 + (d1 - d2) * h - k * h * (1. - h)
+            }
+            Sdf::Repeat(period, sdf) => {
+                let cell = Vec3::select(
+                    period.cmpgt(Vec3::ZERO),
+                    *period * (p / *period).round(),
+                    Vec3::ZERO,
+                );
+                sdf.distance(p - cell)
+            }
+            Sdf::Displace {
+                amplitude,
+                frequency,
+                sdf,
+            } => {
+                let q = p * *frequency;
+                sdf.distance(p) + amplitude * q.x.sin() * q.y.sin() * q.z.sin()
+            }
+        }
+    }
+
+    /// Bounds of the solid, or `None` if it extends infinitely.
+    pub fn bounds(&self) -> Option<Aabb> {
+        let expand = |bounds: Aabb, amount: f32| {
+            Aabb::new(
+                bounds.min - Vec3::splat(amount),
+                bounds.max + Vec3::splat(amount),
+            )
+        };
+
+        match self {
+            Sdf::Sphere { radius } => Some(Aabb::new(Vec3::splat(-radius), Vec3::splat(*radius))),
+            Sdf::Cuboid {
+                half_extents,
+                rounding,
+            } => Some(expand(Aabb::new(-*half_extents, *half_extents), *rounding)),
+            Sdf::Torus {
+                major_radius,
+                minor_radius,
+            } => {
+                let outer = major_radius + minor_radius;
+                Some(Aabb::new(
+                    Vec3::new(-outer, -minor_radius, -outer),
+                    Vec3::new(outer, *minor_radius, outer),
+                ))
+            }
+            Sdf::Capsule { a, b, radius } => Some(expand(Aabb::from_points([*a, *b]), *radius)),
+            Sdf::Translate(offset, sdf) => sdf
+                .bounds()
+                .map(|bounds| Aabb::new(bounds.min + *offset, bounds.max + *offset)),
+            Sdf::Union(a, b) => Some(a.bounds()?.union(b.bounds()?)),
+            Sdf::Intersection(a, b) => match (a.bounds(), b.bounds()) {
+                (Some(a), Some(b)) => Some(Aabb::new(a.min.max(b.min), a.max.min(b.max))),
+                (a, b) => a.or(b),
+            },
+            Sdf::Subtraction(a, _) => a.bounds(),
+            Sdf::SmoothUnion(k, a, b) => Some(expand(a.bounds()?.union(b.bounds()?), *k)),
+            Sdf::Repeat(..) => None,
+            Sdf::Displace { amplitude, sdf, .. } => {
+                sdf.bounds().map(|bounds| expand(bounds, amplitude.abs()))
+            }
+        }
+    }
+
+    /// Upper bound on how fast the function changes per unit of distance. Exact distance
+    /// functions have a constant of one; displacement makes it steeper.
+    fn lipschitz(&self) -> f32 {
+        match self {
+            Sdf::Sphere { .. } | Sdf::Cuboid { .. } | Sdf::Torus { .. } | Sdf::Capsule { .. } => 1.,
+            Sdf::Translate(_, sdf) | Sdf::Repeat(_, sdf) => sdf.lipschitz(),
+            Sdf::Union(a, b)
+            | Sdf::Intersection(a, b)
+            | Sdf::Subtraction(a, b)
+            | Sdf::SmoothUnion(_, a, b) => a.lipschitz().max(b.lipschitz()),
+            Sdf::Displace {
+                amplitude,
+                frequency,
+                sdf,
+            } => sdf.lipschitz() + (amplitude * frequency).abs() * 3f32.sqrt(),
+        }
+    }
+}
+
+/// Object rendering a signed distance function by sphere tracing. Texture coordinates are not
+/// defined and always zero.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistanceField {
+    sdf: Sdf,
+    bounds: Aabb,
+    step_scale: f32,
+    pub material: MaterialId,
+}
+
+impl DistanceField {
+    pub fn new(sdf: Sdf, material: MaterialId) -> Self {
+        let bounds = sdf
+            .bounds()
+            .unwrap_or(Aabb::new(Vec3::splat(-FAR_AWAY), Vec3::splat(FAR_AWAY)));
+
+        DistanceField {
+            step_scale: 1. / sdf.lipschitz(),
+            sdf,
+            bounds,
+            material,
+        }
+    }
+
+    /// Restricts the field to `bounds`, which is required to render infinitely repeated shapes
+    /// efficiently.
+    pub fn with_bounds(mut self, bounds: Aabb) -> Self {
+        self.bounds = bounds;
+        self
+    }
+
+    pub fn sdf(&self) -> &Sdf {
+        &self.sdf
+    }
+
+    fn normal(&self, p: Vec3) -> Vec3 {
+        // tetrahedral central differences, four evaluations instead of six
+        let k = Vec2::new(1., -1.);
+        let (xyy, yyx, yxy, xxx) = (
+            Vec3::new(k.x, k.y, k.y),
+            Vec3::new(k.y, k.y, k.x),
+            Vec3::new(k.y, k.x, k.y),
+            Vec3::splat(k.x),
+        );
+
+        (xyy * self.sdf.distance(p + xyy * GRADIENT_STEP)
+            + yyx * self.sdf.distance(p + yyx * GRADIENT_STEP)
+            + yxy * self.sdf.distance(p + yxy * GRADIENT_STEP)
+            + xxx * self.sdf.distance(p + xxx * GRADIENT_STEP))
+        .normalize()
+    }
+}
+
+impl Intersectable for DistanceField {
+    fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        let inv_direction = ray.direction.recip();
+        let t1 = (self.bounds.min - ray.origin) * inv_direction;
+        let t2 = (self.bounds.max - ray.origin) * inv_direction;
+        let near = t1.min(t2).max_element().max(0.);
+        let far = t1.max(t2).min_element().min(FAR_AWAY);
+        if near > far {
+            return None;
+        }
+
+        // rays starting inside the solid march towards the surface from within
+        let sign = self.sdf.distance(ray.at(near)).signum();
+
+        let mut t = near;
+        for _ in 0..MAX_STEPS {
+            if t > far {
+                return None;
+            }
+
+            let d = sign * self.sdf.distance(ray.at(t)) * self.step_scale;
+            if d < HIT_DISTANCE && t > MIN_HIT_DISTANCE {
+                return Some(Hit {
+                    distance: t,
+                    normal: self.normal(ray.at(t)),
+                    uv: Vec2::ZERO,
+                    material: self.material,
+                    color: None,
+                });
+            }
+
+            t += d.max(HIT_DISTANCE);
+        }
+
+        None
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hit() {
+        let field = DistanceField::new(
+            Sdf::sphere(1.).translate(Vec3::new(0., 0., -5.)),
+            MaterialId(0),
+        );
+        let hit = field
+            .intersect(&Ray::new(Vec3::ZERO, Vec3::NEG_Z, 0.))
+            .unwrap();
+
+        assert!((hit.distance - 4.).abs() < 1e-3);
+        assert!((hit.normal - Vec3::Z).length() < 1e-2);
+    }
+
+    #[test]
+    fn miss() {
+        let field = DistanceField::new(
+            Sdf::sphere(1.).translate(Vec3::new(0., 0., -5.)),
+            MaterialId(0),
+        );
+        let ray = Ray::new(Vec3::new(2., 0., 0.), Vec3::NEG_Z, 0.);
+
+        assert!(field.intersect(&ray).is_none());
+    }
+
+    #[test]
+    fn inside() {
+        let field = DistanceField::new(Sdf::cuboid(Vec3::ONE, 0.), MaterialId(0));
+        let hit = field.intersect(&Ray::new(Vec3::ZERO, Vec3::X, 0.)).unwrap();
+
+        assert!((hit.distance - 1.).abs() < 1e-3);
+        assert!((hit.normal - Vec3::X).length() < 1e-2);
+    }
+
+    #[test]
+    fn smooth_union_fills_the_gap() {
+        let a = Sdf::sphere(1.).translate(Vec3::new(-1.1, 0., 0.));
+        let b = Sdf::sphere(1.).translate(Vec3::new(1.1, 0., 0.));
+        let ray = Ray::new(Vec3::new(0., 5., 0.), Vec3::NEG_Y, 0.);
+
+        let sharp = DistanceField::new(a.clone().union(b.clone()), MaterialId(0));
+        assert!(sharp.intersect(&ray).is_none());
+
+        let smooth = DistanceField::new(a.smooth_union(b, 0.5), MaterialId(0));
+        assert!(smooth.intersect(&ray).is_some());
+    }
+
+    #[test]
+    fn repetition() {
+        let field = DistanceField::new(
+            Sdf::sphere(0.5).repeat(Vec3::new(4., 0., 0.)),
+            MaterialId(0),
+        )
+        .with_bounds(Aabb::new(Vec3::new(-10., -1., -1.), Vec3::new(10., 1., 1.)));
+        let ray = Ray::new(Vec3::new(8., 5., 0.), Vec3::NEG_Y, 0.);
+        let hit = field.intersect(&ray).unwrap();
+
+        assert!((hit.distance - 4.5).abs() < 1e-3);
+    }
+}
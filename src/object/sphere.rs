@@ -1,54 +1,77 @@
-use glam::Vec3;
+use std::f32::consts::PI;
 
-use crate::surface::SurfaceMaterial;
+use glam::{Vec2, Vec3};
 
-use super::Intersectable;
+use crate::{aabb::Aabb, consts::MIN_HIT_DISTANCE, object_store::MaterialId, ray::Ray};
+
+use super::{Hit, Intersectable, Interval};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Sphere {
     pub center: Vec3,
     pub radius: f32,
-    pub material: SurfaceMaterial,
+    pub material: MaterialId,
 }
 
 impl Sphere {
-    pub fn new(center: Vec3, radius: f32, material: SurfaceMaterial) -> Self {
+    pub fn new(center: Vec3, radius: f32, material: MaterialId) -> Self {
         Sphere {
             center,
             radius,
             material,
         }
     }
-}
-
-impl Intersectable for Sphere {
-    fn intersect(&self, pos: Vec3, dir: Vec3) -> Option<f32> {
-        let adj = pos - self.center;
 
-        let b = adj.x * dir.x + adj.y * dir.y + adj.z * dir.z;
+    /// Distances at which the line of `ray` crosses the sphere, in ascending order.
+    fn crossings(&self, ray: &Ray) -> Option<(f32, f32)> {
+        let adj = ray.origin - self.center;
+        let b = adj.dot(ray.direction);
         let t = b.powi(2) - adj.length_squared() + self.radius.powi(2);
         if t < 0. {
             return None;
         }
 
-        let s1 = -b - t.sqrt();
-        if s1 > 0. {
-            return Some(s1);
-        }
+        Some((-b - t.sqrt(), -b + t.sqrt()))
+    }
 
-        let s2 = -b + t.sqrt();
-        if s2 > 0. {
-            return Some(s2);
+    fn hit(&self, ray: &Ray, distance: f32) -> Hit {
+        let normal = (ray.at(distance) - self.center) / self.radius;
+
+        Hit {
+            distance,
+            normal,
+            uv: Vec2::new(
+                0.5 + normal.z.atan2(normal.x) / (2. * PI),
+                0.5 + normal.y.clamp(-1., 1.).asin() / PI,
+            ),
+            material: self.material,
+            color: None,
         }
+    }
+}
 
-        None
+impl Intersectable for Sphere {
+    fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        let (near, far) = self.crossings(ray)?;
+        let distance = [near, far]
+            .into_iter()
+            .find(|distance| *distance > MIN_HIT_DISTANCE)?;
+        Some(self.hit(ray, distance))
     }
 
-    fn normal(&self, pos: Vec3) -> Vec3 {
-        (pos - self.center) / self.radius
+    fn intervals(&self, ray: &Ray, intervals: &mut Vec<Interval>) {
+        if let Some((enter, exit)) = self.crossings(ray) {
+            intervals.push(Interval {
+                enter: self.hit(ray, enter),
+                exit: self.hit(ray, exit),
+            });
+        }
     }
 
-    fn surface(&self) -> &SurfaceMaterial {
-        &self.material
+    fn bounds(&self) -> Aabb {
+        Aabb::new(
+            self.center - Vec3::splat(self.radius),
+            self.center + Vec3::splat(self.radius),
+        )
     }
 }
@@ -0,0 +1,173 @@
+use std::f32::consts::PI;
+
+use glam::{Vec2, Vec3};
+
+use crate::{
+    aabb::Aabb, consts::MIN_HIT_DISTANCE, object_store::MaterialId, ray::Ray, solver::solve_quartic,
+};
+
+use super::{frame::Frame, Hit, Intersectable, Interval};
+
+/// Torus around `axis`, with the tube of `minor_radius` centered `major_radius` away from the
+/// axis. `u` runs around the axis and `v` around the tube.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Torus {
+    pub major_radius: f32,
+    pub minor_radius: f32,
+    pub material: MaterialId,
+    frame: Frame,
+}
+
+impl Torus {
+    pub fn new(
+        center: Vec3,
+        axis: Vec3,
+        major_radius: f32,
+        minor_radius: f32,
+        material: MaterialId,
+    ) -> Self {
+        Torus {
+            major_radius,
+            minor_radius,
+            material,
+            frame: Frame::from_axis(center, axis),
+        }
+    }
+}
+
+impl Torus {
+    /// Distances at which the line of the local-space ray crosses the torus, in ascending order,
+    /// and how many of the four slots are used.
+    fn crossings(&self, local: &Ray) -> ([f32; 4], usize) {
+        // Start the ray where it enters the bounding sphere to keep the quartic well conditioned.
+        let bounding_radius = self.major_radius + self.minor_radius;
+        let b = local.origin.dot(local.direction);
+        let c = local.origin.length_squared() - bounding_radius * bounding_radius;
+        let discriminant = b * b - c;
+        if discriminant < 0. {
+            return ([0.; 4], 0);
+        }
+        let offset = -b - discriminant.sqrt();
+
+        let o = local.at(offset).as_dvec3();
+        let d = local.direction.as_dvec3();
+        let r2 = (self.major_radius as f64).powi(2);
+        let m2 = (self.minor_radius as f64).powi(2);
+
+        // (|p|² + R² - r²)² = 4 R² (x² + z²), with |d| = 1
+        let od = o.dot(d);
+        let k = o.length_squared() + r2 - m2;
+        let roots = solve_quartic([
+            k * k - 4. * r2 * (o.x * o.x + o.z * o.z),
+            4. * k * od - 8. * r2 * (o.x * d.x + o.z * d.z),
+            2. * k + 4. * od * od - 4. * r2 * (d.x * d.x + d.z * d.z),
+            4. * od,
+            1.,
+        ]);
+
+        let mut crossings = [0.; 4];
+        for (crossing, root) in crossings.iter_mut().zip(roots.iter()) {
+            *crossing = *root as f32 + offset;
+        }
+        crossings[..roots.len()].sort_by(f32::total_cmp);
+        (crossings, roots.len())
+    }
+
+    fn hit(&self, local: &Ray, distance: f32) -> Hit {
+        let p = local.at(distance);
+        let s = p.length_squared() + self.major_radius.powi(2) - self.minor_radius.powi(2);
+        let r = 2. * self.major_radius.powi(2);
+        let normal = Vec3::new(p.x * (s - r), p.y * s, p.z * (s - r)).normalize();
+
+        let ring = (p.x * p.x + p.z * p.z).sqrt() - self.major_radius;
+        let uv = Vec2::new(
+            0.5 + p.z.atan2(p.x) / (2. * PI),
+            0.5 + p.y.atan2(ring) / (2. * PI),
+        );
+
+        Hit {
+            distance,
+            normal: self.frame.world_normal(normal),
+            uv,
+            material: self.material,
+            color: None,
+        }
+    }
+}
+
+impl Intersectable for Torus {
+    fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        let local = self.frame.local_ray(ray);
+
+        let (crossings, count) = self.crossings(&local);
+        let distance = *crossings[..count].iter().find(|t| **t > MIN_HIT_DISTANCE)?;
+        Some(self.hit(&local, distance))
+    }
+
+    fn intervals(&self, ray: &Ray, intervals: &mut Vec<Interval>) {
+        let local = self.frame.local_ray(ray);
+
+        // the line starts and ends outside, so crossings alternate between entering and leaving
+        let (crossings, count) = self.crossings(&local);
+        for pair in crossings[..count].chunks_exact(2) {
+            intervals.push(Interval {
+                enter: self.hit(&local, pair[0]),
+                exit: self.hit(&local, pair[1]),
+            });
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        let outer = self.major_radius + self.minor_radius;
+        self.frame.bounds(Aabb::new(
+            Vec3::new(-outer, -self.minor_radius, -outer),
+            Vec3::new(outer, self.minor_radius, outer),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn torus() -> Torus {
+        Torus::new(Vec3::ZERO, Vec3::Y, 2., 0.5, MaterialId(0))
+    }
+
+    #[test]
+    fn hit() {
+        let ray = Ray::new(Vec3::new(-5., 0., 0.), Vec3::X, 0.);
+        let hit = torus().intersect(&ray).unwrap();
+
+        assert!((hit.distance - 2.5).abs() < 1e-3);
+        assert!((hit.normal - Vec3::NEG_X).length() < 1e-3);
+    }
+
+    #[test]
+    fn hit_from_above() {
+        let ray = Ray::new(Vec3::new(2., 5., 0.), Vec3::NEG_Y, 0.);
+        let hit = torus().intersect(&ray).unwrap();
+
+        assert!((hit.distance - 4.5).abs() < 1e-3);
+        assert!((hit.normal - Vec3::Y).length() < 1e-3);
+    }
+
+    #[test]
+    fn miss() {
+        // straight through the hole
+        let ray = Ray::new(Vec3::new(0., 5., 0.), Vec3::NEG_Y, 0.);
+        assert!(torus().intersect(&ray).is_none());
+
+        let above = Ray::new(Vec3::new(-5., 1., 0.), Vec3::X, 0.);
+        assert!(torus().intersect(&above).is_none());
+    }
+
+    #[test]
+    fn inside() {
+        let ray = Ray::new(Vec3::new(2., 0., 0.), Vec3::Y, 0.);
+        let hit = torus().intersect(&ray).unwrap();
+
+        assert!((hit.distance - 0.5).abs() < 1e-3);
+        assert!((hit.normal - Vec3::Y).length() < 1e-3);
+    }
+}
@@ -1,8 +1,8 @@
-use glam::Vec3;
+use glam::{Vec2, Vec3};
 
-use crate::surface::SurfaceMaterial;
+use crate::{aabb::Aabb, consts::MIN_HIT_DISTANCE, object_store::MaterialId, ray::Ray};
 
-use super::Intersectable;
+use super::{Hit, Intersectable};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Triangle {
@@ -10,11 +10,11 @@ pub struct Triangle {
     edge_a: Vec3,
     edge_b: Vec3,
     normal: Vec3,
-    material: SurfaceMaterial,
+    material: MaterialId,
 }
 
 impl Triangle {
-    pub fn from_vertices(a: Vec3, b: Vec3, c: Vec3, material: SurfaceMaterial) -> Self {
+    pub fn from_vertices(a: Vec3, b: Vec3, c: Vec3, material: MaterialId) -> Self {
         let edge_a = b - a;
         let edge_b = c - a;
         let normal = edge_a.cross(edge_b).normalize();
@@ -30,39 +30,59 @@ impl Triangle {
 }
 
 impl Intersectable for Triangle {
-    fn intersect(&self, pos: Vec3, ray: Vec3) -> Option<f32> {
-        let ray_cross_e2 = ray.cross(self.edge_b);
-        let det = self.edge_a.dot(ray_cross_e2);
-        if det.abs() < f32::EPSILON {
-            return None; // ray is parallel to the triangle
-        }
+    fn intersect(&self, ray: &Ray) -> Option<Hit> {
+        let (t, u, v) = intersect_triangle(self.origin, self.edge_a, self.edge_b, ray)?;
 
-        let inv_det = 1. / det;
-        let s = pos - self.origin;
-        let u = inv_det * s.dot(ray_cross_e2);
-        if u < 0. || u > 1. {
-            return None;
-        }
+        Some(Hit {
+            distance: t,
+            normal: self.normal,
+            uv: Vec2::new(u, v),
+            material: self.material,
+            color: None,
+        })
+    }
 
-        let s_cross_e1 = s.cross(self.edge_a);
-        let v = inv_det * ray.dot(s_cross_e1);
-        if v < 0. || u + v > 1. {
-            return None;
-        }
+    fn bounds(&self) -> Aabb {
+        Aabb::from_points([
+            self.origin,
+            self.origin + self.edge_a,
+            self.origin + self.edge_b,
+        ])
+    }
+}
 
-        let t = inv_det * self.edge_b.dot(s_cross_e1);
-        if t > f32::EPSILON {
-            Some(t)
-        } else {
-            None
-        }
+/// Möller–Trumbore intersection of a ray with the triangle spanned by `edge_a` and `edge_b` from
+/// `origin`. Returns the distance along the ray and the barycentric coordinates of the hit.
+pub(super) fn intersect_triangle(
+    origin: Vec3,
+    edge_a: Vec3,
+    edge_b: Vec3,
+    ray: &Ray,
+) -> Option<(f32, f32, f32)> {
+    let (pos, ray) = (ray.origin, ray.direction);
+    let ray_cross_e2 = ray.cross(edge_b);
+    let det = edge_a.dot(ray_cross_e2);
+    if det.abs() < f32::EPSILON {
+        return None; // ray is parallel to the triangle
+    }
+
+    let inv_det = 1. / det;
+    let s = pos - origin;
+    let u = inv_det * s.dot(ray_cross_e2);
+    if !(0. ..=1.).contains(&u) {
+        return None;
     }
 
-    fn normal(&self, _pos: Vec3) -> Vec3 {
-        self.normal
+    let s_cross_e1 = s.cross(edge_a);
+    let v = inv_det * ray.dot(s_cross_e1);
+    if v < 0. || u + v > 1. {
+        return None;
     }
 
-    fn surface(&self) -> &SurfaceMaterial {
-        &self.material
+    let t = inv_det * edge_b.dot(s_cross_e1);
+    if t > MIN_HIT_DISTANCE {
+        Some((t, u, v))
+    } else {
+        None
     }
 }
@@ -1,182 +1,290 @@
-use glam::{vec3, Vec3};
-use once_cell::sync::Lazy;
+use std::{cell::Cell, path::Path};
+
+use eyre::Result;
+use glam::vec3;
 
 use crate::{
-    camera::{Camera, CameraDescriptor},
+    animation::{Animation, Channel, Interpolation, Track},
+    camera::{Camera, CameraDescriptor, Projection, RenderMode},
     color::Color,
+    consts::FAR_AWAY,
+    import::obj::ObjOptions,
     light::Light,
-    object::{sphere::Sphere, triangle::Triangle, Object},
-    surface::SurfaceMaterial,
+    material::Material,
+    medium::Volume,
+    object::{Hit, Intersectable, Object},
+    ray::Ray,
 };
 
-pub static mut OBJECT_STORE: Lazy<ObjectStore> = Lazy::new(|| ObjectStore::new());
+/// Handle of an object in the [`ObjectStore`]. Objects are never removed from the store, so a
+/// handle stays valid and refers to the same object for the lifetime of the store.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ObjectId(pub(crate) u32);
+
+/// Handle of a material in the [`ObjectStore`]'s material table, stable like [`ObjectId`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MaterialId(pub(crate) u32);
 
+#[derive(Default)]
 pub struct ObjectStore {
     pub camera: Camera,
     objects: Vec<Object>,
     lights: Vec<Light>,
+    materials: Vec<Material>,
+    material_names: Vec<String>,
+    volumes: Vec<Volume>,
+    pub animation: Animation,
+    /// Ray-object tests since the count was last taken.
+    intersection_tests: Cell<u64>,
 }
 
 impl ObjectStore {
     pub fn new() -> Self {
-        ObjectStore {
-            camera: Camera::default(),
-            objects: Vec::new(),
-            lights: Vec::new(),
-        }
+        Self::default()
     }
 
-    pub fn read_scene(&mut self, width: u32, height: u32) {
-        println!("width: {}, height: {}", width, height);
-        let camera = Camera::new(&CameraDescriptor {
-            width,
-            height,
-            vfov: 50.,
-            eye_pointer: vec3(0., 1., 2.),
-            look_pointer: vec3(0., 0.5, 0.),
-            up_pointer: vec3(0., 1., 0.),
-            max_level: 5,
-            background: Color::new(150., 10., 50.),
-            near: 0.0001,
-            far: 10000.,
-        });
-        self.camera = camera;
+    /// Replaces the contents of the store with the default scene: the Cornell box OBJ at `path`,
+    /// lit by a point light inside it and viewed by a camera circling it.
+    pub fn read_scene(&mut self, path: impl AsRef<Path>, width: u32, height: u32) -> Result<()> {
+        self.read_scene_with(path, &ObjOptions::default(), width, height)
+    }
 
-        let light = Light::new(vec3(0., 1.2, 0.), 0.12);
-        self.lights.push(light);
+    /// Like [`ObjectStore::read_scene`], loading the OBJ with `options`, for instance to subdivide
+    /// it.
+    pub fn read_scene_with(
+        &mut self,
+        path: impl AsRef<Path>,
+        options: &ObjOptions,
+        width: u32,
+        height: u32,
+    ) -> Result<()> {
+        let path = path.as_ref();
+        log::debug!("loading {} at {width}x{height}", path.display());
+
+        // four second turntable around the box
+        let turntable = (0..=8).fold(Track::new(Interpolation::CatmullRom), |track, i| {
+            let angle = i as f32 * std::f32::consts::FRAC_PI_4;
+            track.key(i as f32 * 0.5, vec3(2. * angle.sin(), 1., 2. * angle.cos()))
+        });
 
-        let cornell_box = tobj::load_obj("CornellBox-Original.obj", &tobj::GPU_LOAD_OPTIONS)
-            .expect("object file to be loaded");
-        let (models, materials) = cornell_box;
-        let materials = materials.expect("materials to be loaded");
-
-        let materials = materials
-            .iter()
-            .map(|m| {
-                let ambient = m.ambient.unwrap_or_default();
-                let diffuse = m.diffuse.unwrap_or_default();
-                println!("material: {:?}", m.name);
-                println!("other: {:?}", m.unknown_param);
-                SurfaceMaterial {
-                    ambient: ambient.into(),
-                    diffuse: diffuse.into(),
-                    specular: m.specular.unwrap_or_default().into(),
-                    specular_power: m.shininess.unwrap_or(0.),
-                    reflection: 0.,
-                    transparency: m.dissolve.unwrap_or(0.),
-                }
+        *self = ObjectStore::builder()
+            .camera(CameraDescriptor {
+                width,
+                height,
+                vfov: 50.,
+                eye_pointer: vec3(0., 1., 2.),
+                look_pointer: vec3(0., 0.5, 0.),
+                up_pointer: vec3(0., 1., 0.),
+                projection: Projection::Perspective,
+                shutter_open: 0.,
+                shutter_close: 1.,
+                motion: None,
+                samples: 1,
+                glossy_samples: 1,
+                volume_steps: 16,
+                max_level: 5,
+                ray_budget: None,
+                background: Color::new(150., 10., 50.),
+                ambient_occlusion: None,
+                render_mode: RenderMode::Shaded,
+                near: 0.0001,
+                far: 10000.,
             })
-            .collect::<Vec<_>>();
-
-        for model in models {
-            println!("model: {:?}", model.name);
-            let mesh = model.mesh;
-
-            let positions = mesh
-                .positions
-                .chunks(3)
-                .map(|p| vec3(p[0], p[1], p[2]))
-                .collect::<Vec<_>>();
-
-            let normals = mesh
-                .normals
-                .chunks(3)
-                .map(|n| vec3(n[0], n[1], n[2]))
-                .collect::<Vec<_>>();
-
-            let indices = mesh
-                .indices
-                .chunks(3)
-                .map(|i| (i[0] as usize, i[1] as usize, i[2] as usize))
-                .collect::<Vec<_>>();
-
-            let material = &materials[mesh.material_id.unwrap_or(0)];
-
-            let triangles = indices
-                .iter()
-                .map(|(i0, i1, i2)| {
-                    let a = positions[*i0];
-                    let b = positions[*i1];
-                    let c = positions[*i2];
-
-                    Triangle::from_vertices(a, b, c, material.clone()).into()
-                })
-                .collect::<Vec<_>>();
-
-            self.objects.extend(triangles);
-        }
+            .point_light(vec3(0., 1.2, 0.), 0.12)
+            .mesh_from_obj_with(path, options)
+            .animation(Animation {
+                channels: vec![Channel::CameraEye(turntable)],
+                ..Default::default()
+            })
+            .build()?;
+
+        Ok(())
+    }
 
-        // self.objects.push(
-        //     Sphere::new(
-        //         Vec3::ZERO,
-        //         3.,
-        //         SurfaceMaterial {
-        //             ambient: Color::new(0., 0., 0.),
-        //             diffuse: Color::new(200., 0., 0.),
-        //             specular: Color::new(200., 0., 0.),
-        //             specular_power: 15.,
-        //             reflection: 0.6,
-        //             transparency: 0.,
-        //         },
-        //     )
-        //     .into(),
-        // );
-
-        // self.objects.push(
-        //     Sphere::new(
-        //         vec3(2., 1., 5.),
-        //         1.,
-        //         SurfaceMaterial {
-        //             ambient: Color::new(0., 50., 0.),
-        //             diffuse: Color::new(0., 100., 0.),
-        //             specular: Color::new(30., 40., 30.),
-        //             specular_power: 2.,
-        //             reflection: 1.,
-        //             transparency: 0.,
-        //         },
-        //     )
-        //     .into(),
-        // );
-
-        // self.objects.push(
-        //     Sphere::new(
-        //         vec3(-2., 1., 5.),
-        //         1.,
-        //         SurfaceMaterial {
-        //             ambient: Color::new(0., 0., 50.),
-        //             diffuse: Color::new(0., 0., 100.),
-        //             specular: Color::new(30., 30., 40.),
-        //             specular_power: 2.,
-        //             reflection: 0.3,
-        //             transparency: 0.,
-        //         },
-        //     )
-        //     .into(),
-        // );
-
-        // self.objects.push(
-        //     Triangle::from_vertices(
-        //         vec3(2., 1., 6.),
-        //         vec3(-2., 1., 6.),
-        //         vec3(0., 0., 6.),
-        //         SurfaceMaterial {
-        //             ambient: Color::new(0., 50., 0.),
-        //             diffuse: Color::new(0., 200., 0.),
-        //             specular: Color::new(30., 60., 0.),
-        //             specular_power: 6.,
-        //             reflection: 0.2,
-        //             transparency: 0.,
-        //         },
-        //     )
-        //     .into(),
-        // );
+    pub fn add_light(&mut self, light: Light) {
+        self.lights.push(light);
+    }
+
+    pub fn lights(&self) -> &[Light] {
+        &self.lights
     }
 
     pub fn lights_mut(&mut self) -> impl Iterator<Item = &mut Light> {
         self.lights.iter_mut()
     }
 
+    /// Adds a participating medium such as fog to the scene.
+    pub fn add_volume(&mut self, volume: Volume) {
+        self.volumes.push(volume);
+    }
+
+    pub fn volumes(&self) -> &[Volume] {
+        &self.volumes
+    }
+
     pub fn objects(&self) -> &[Object] {
         &self.objects
     }
+
+    /// Adds an object to the scene and returns its handle.
+    pub fn add_object(&mut self, object: impl Into<Object>) -> ObjectId {
+        self.objects.push(object.into());
+        ObjectId(self.objects.len() as u32 - 1)
+    }
+
+    pub fn object(&self, id: ObjectId) -> Option<&Object> {
+        self.objects.get(id.0 as usize)
+    }
+
+    pub fn object_mut(&mut self, id: ObjectId) -> Option<&mut Object> {
+        self.objects.get_mut(id.0 as usize)
+    }
+
+    /// Adds a material to the material table and returns its handle.
+    pub fn add_material(&mut self, name: &str, material: impl Into<Material>) -> MaterialId {
+        self.materials.push(material.into());
+        self.material_names.push(name.to_owned());
+        MaterialId(self.materials.len() as u32 - 1)
+    }
+
+    pub fn material(&self, id: MaterialId) -> &Material {
+        &self.materials[id.0 as usize]
+    }
+
+    pub fn material_name(&self, id: MaterialId) -> &str {
+        &self.material_names[id.0 as usize]
+    }
+
+    pub fn material_mut(&mut self, id: MaterialId) -> Option<&mut Material> {
+        self.materials.get_mut(id.0 as usize)
+    }
+
+    /// Finds the closest hit in front of the ray origin and the object it belongs to.
+    pub fn intersect(&self, ray: &Ray) -> Option<(ObjectId, Hit)> {
+        let mut closest_distance = FAR_AWAY;
+        let mut closest = None;
+
+        self.count_tests(self.objects.len());
+        for (i, object) in self.objects.iter().enumerate() {
+            let Some(hit) = object.intersect(ray) else {
+                continue;
+            };
+
+            if hit.distance > 0. && hit.distance <= closest_distance {
+                closest_distance = hit.distance;
+                closest = Some((ObjectId(i as u32), hit));
+            }
+        }
+
+        closest
+    }
+
+    /// Returns whether anything blocks `ray` within `max_distance` of its origin. Stops at the
+    /// first blocker found, so it is cheaper than [`ObjectStore::intersect`].
+    pub fn occluded(&self, ray: &Ray, max_distance: f32) -> bool {
+        self.objects.iter().any(|object| {
+            self.count_tests(1);
+            object
+                .intersect(ray)
+                .is_some_and(|hit| hit.distance > 0. && hit.distance <= max_distance)
+        })
+    }
+
+    /// Returns the brightness of `light` arriving at the origin of the shadow `ray`, or zero if
+    /// anything blocks the ray on its way to the light.
+    pub fn light_brightness(&self, light: &Light, ray: &Ray) -> f32 {
+        if self.occluded(ray, light.position.distance(ray.origin)) {
+            0.
+        } else {
+            light.brightness
+        }
+    }
+
+    /// Returns the number of ray-object tests since the last call and restarts the count.
+    pub fn take_intersection_tests(&self) -> u64 {
+        self.intersection_tests.take()
+    }
+
+    fn count_tests(&self, n: usize) {
+        self.intersection_tests
+            .set(self.intersection_tests.get() + n as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::*;
+    use crate::{
+        object::{mesh::Mesh, sphere::Sphere},
+        stats::take_traversal_tests,
+        surface::SurfaceMaterial,
+    };
+
+    #[test]
+    fn hits_report_object_and_material() {
+        let mut store = ObjectStore::new();
+        let red = store.add_material("red", SurfaceMaterial::default());
+        let blue = store.add_material("blue", SurfaceMaterial::default());
+        let near = store.add_object(Sphere::new(vec3(0., 0., -3.), 1., red));
+        let far = store.add_object(Sphere::new(vec3(0., 0., -6.), 1., blue));
+        assert_ne!(near, far);
+
+        let (object, hit) = store
+            .intersect(&Ray::new(Vec3::ZERO, Vec3::NEG_Z, 0.))
+            .unwrap();
+        assert_eq!(object, near);
+        assert_eq!(hit.material, red);
+
+        let (object, hit) = store
+            .intersect(&Ray::new(vec3(0., 0., -4.5), Vec3::NEG_Z, 0.))
+            .unwrap();
+        assert_eq!(object, far);
+        assert_eq!(hit.material, blue);
+    }
+
+    #[test]
+    fn occlusion_stops_at_max_distance() {
+        let mut store = ObjectStore::new();
+        let material = store.add_material("white", SurfaceMaterial::default());
+        store.add_object(Sphere::new(vec3(0., 0., -3.), 1., material));
+
+        let ray = Ray::new(Vec3::ZERO, Vec3::NEG_Z, 0.);
+        assert!(store.occluded(&ray, 5.));
+        assert!(!store.occluded(&ray, 1.5));
+        assert!(!store.occluded(&Ray::new(Vec3::ZERO, Vec3::Z, 0.), 5.));
+    }
+
+    #[test]
+    fn counts_tests_inside_meshes() {
+        let mut store = ObjectStore::new();
+        let material = store.add_material("white", SurfaceMaterial::default());
+        // a strip of 16 quads along x, in the z = -3 plane
+        let positions = (0..=16)
+            .flat_map(|i| [vec3(i as f32, 0., -3.), vec3(i as f32, 1., -3.)])
+            .collect();
+        let triangles = (0..16)
+            .flat_map(|i| [[2 * i, 2 * i + 2, 2 * i + 3], [2 * i, 2 * i + 3, 2 * i + 1]])
+            .collect();
+        store.add_object(Mesh::new(
+            positions,
+            Vec::new(),
+            Vec::new(),
+            triangles,
+            material,
+        ));
+
+        take_traversal_tests();
+        assert!(store
+            .intersect(&Ray::new(vec3(0.5, 0.5, 0.), Vec3::NEG_Z, 0.))
+            .is_some());
+        assert_eq!(store.take_intersection_tests(), 1);
+
+        // the hierarchy skips most of the triangles
+        let (nodes, triangles) = take_traversal_tests();
+        assert!(nodes > 0);
+        assert!((1..32).contains(&triangles), "{triangles}");
+        assert_eq!(take_traversal_tests(), (0, 0));
+    }
 }
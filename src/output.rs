@@ -0,0 +1,21 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use eyre::{Result, WrapErr};
+
+/// Writes a tightly packed 8-bit RGB buffer as a PNG image.
+pub fn write_png(path: &Path, width: u32, height: u32, rgb: &[u8]) -> Result<()> {
+    let file = File::create(path).wrap_err_with(|| format!("creating {}", path.display()))?;
+
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder
+        .write_header()
+        .wrap_err_with(|| format!("writing header of {}", path.display()))?;
+    writer
+        .write_image_data(rgb)
+        .wrap_err_with(|| format!("writing image data of {}", path.display()))?;
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use glam::Vec3;
+
+/// Distance secondary rays are moved off the surface they start on, relative to the magnitude of
+/// the hit position so the offset stays above its floating point error.
+const ORIGIN_OFFSET: f32 = 1e-4;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+    pub time: f32,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3, time: f32) -> Self {
+        Ray {
+            origin,
+            direction,
+            time,
+        }
+    }
+
+    /// Creates a ray leaving a surface at `origin`. The origin is offset along `normal` towards
+    /// the side `direction` points to, so the ray can't hit the surface it starts on again while
+    /// still hitting any other part of the same object.
+    pub fn spawn(origin: Vec3, normal: Vec3, direction: Vec3, time: f32) -> Self {
+        let offset = ORIGIN_OFFSET * (1. + origin.abs().max_element());
+        let normal = if direction.dot(normal) < 0. {
+            -normal
+        } else {
+            normal
+        };
+
+        Ray::new(origin + normal * offset, direction, time)
+    }
+
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+}
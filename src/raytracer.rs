@@ -1,29 +1,238 @@
-use crate::object_store::OBJECT_STORE;
+use std::{mem, ops::ControlFlow, path::Path};
 
-pub struct Raytracer {}
+use eyre::Result;
+
+use crate::{
+    camera::{Camera, RenderProgress},
+    color::Color,
+    import::obj::ObjOptions,
+    inspect::RayTrace,
+    object::Hit,
+    object_store::{ObjectId, ObjectStore},
+    ray::Ray,
+    scene::Scene,
+    stats::RenderStats,
+};
+
+/// Renders a scene it owns.
+pub struct Raytracer {
+    scene: Scene,
+}
+
+/// Result of tracing a single ray through the scene.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trace {
+    /// Closest hit and the object it belongs to, `None` if the ray left the scene.
+    pub hit: Option<(ObjectId, Hit)>,
+    /// Shaded radiance arriving along the ray; the background color if nothing was hit.
+    pub radiance: Color,
+}
 
 impl Raytracer {
-    pub fn new(width: u32, height: u32) -> Self {
-        unsafe { OBJECT_STORE.read_scene(width, height) };
+    /// Creates a raytracer rendering the default scene around the Cornell box OBJ at `path`.
+    pub fn new(path: impl AsRef<Path>, width: u32, height: u32) -> Result<Self> {
+        Self::new_with(path, &ObjOptions::default(), width, height)
+    }
+
+    /// Like [`Raytracer::new`], loading the OBJ with `options`.
+    pub fn new_with(
+        path: impl AsRef<Path>,
+        options: &ObjOptions,
+        width: u32,
+        height: u32,
+    ) -> Result<Self> {
+        let mut scene = ObjectStore::new();
+        scene.read_scene_with(path, options, width, height)?;
+        scene.camera.calculate_first_ray();
+
+        Ok(Raytracer { scene })
+    }
+
+    /// Creates a raytracer rendering `scene` instead of the default one.
+    pub fn with_scene(scene: Scene) -> Self {
+        Raytracer { scene }
+    }
+
+    pub fn render(&mut self) -> Vec<u8> {
+        self.with_camera(|camera, scene| camera.trace(scene));
+
+        self.frame_bytes()
+    }
+
+    /// Renders like [`Raytracer::render`], reporting the progress to `progress` after every row
+    /// of pixels. `progress` may cancel the render by returning [`ControlFlow::Break`], in which
+    /// case `None` is returned.
+    pub fn render_with_progress(
+        &mut self,
+        progress: impl FnMut(RenderProgress) -> ControlFlow<()>,
+    ) -> Option<Vec<u8>> {
+        let flow = self.with_camera(|camera, scene| camera.trace_with_progress(scene, progress));
 
-        unsafe { OBJECT_STORE.camera.calculate_first_ray() };
+        flow.is_continue().then(|| self.frame_bytes())
+    }
 
-        Raytracer {}
+    /// Returns the ray counts and timings of the last rendered frame.
+    pub fn stats(&self) -> RenderStats {
+        self.scene.camera.stats().clone()
     }
 
-    pub fn render(&self) -> Vec<u8> {
-        unsafe { OBJECT_STORE.camera.trace() };
+    /// Poses the scene's animation at `frame` and renders it.
+    pub fn render_frame(&mut self, frame: u32) -> Vec<u8> {
+        self.pose(frame);
+        self.render()
+    }
 
-        let frame_buffer = unsafe { OBJECT_STORE.camera.frame_buffer() };
-        frame_buffer
+    /// Poses the scene's animation at `frame` and renders it like
+    /// [`Raytracer::render_with_progress`].
+    pub fn render_frame_with_progress(
+        &mut self,
+        frame: u32,
+        progress: impl FnMut(RenderProgress) -> ControlFlow<()>,
+    ) -> Option<Vec<u8>> {
+        self.pose(frame);
+        self.render_with_progress(progress)
+    }
+
+    fn pose(&mut self, frame: u32) {
+        let animation = mem::take(&mut self.scene.animation);
+        animation.apply(&mut self.scene, frame);
+        self.scene.animation = animation;
+    }
+
+    /// Calls `f` with the scene's camera taken out of the scene, so that the camera can render
+    /// into its frame buffer while reading the rest of the scene.
+    fn with_camera<R>(&mut self, f: impl FnOnce(&mut Camera, &Scene) -> R) -> R {
+        let mut camera = mem::take(&mut self.scene.camera);
+        let result = f(&mut camera, &self.scene);
+        self.scene.camera = camera;
+        result
+    }
+
+    fn frame_bytes(&self) -> Vec<u8> {
+        self.scene
+            .camera
+            .frame_buffer()
             .iter()
             .flat_map(|pixel| pixel.to_rgb_bytes())
             .collect()
     }
 
-    pub fn resize(&self, width: u32, height: u32) {
-        unsafe {
-            OBJECT_STORE.camera.resize(width, height);
-        }
+    /// Traces `ray` through the scene, returning both the closest hit and the shaded radiance.
+    pub fn trace_ray(&self, ray: &Ray) -> Trace {
+        let hit = self.intersect(ray);
+        let radiance =
+            self.scene
+                .camera
+                .radiance(&self.scene, ray, hit.as_ref().map(|(_, hit)| hit));
+        Trace { hit, radiance }
+    }
+
+    /// Traces the primary ray through the continuous pixel coordinate `(x, y)` at the time the
+    /// shutter opens. Returns `None` if the coordinate lies outside of the camera's projection.
+    pub fn trace_pixel(&self, x: f32, y: f32) -> Option<Trace> {
+        let camera = &self.scene.camera;
+        let ray = camera.primary_ray(x, y, camera.shutter_open)?;
+        Some(self.trace_ray(&ray))
+    }
+
+    /// Finds the closest hit along `ray` without shading it.
+    pub fn intersect(&self, ray: &Ray) -> Option<(ObjectId, Hit)> {
+        self.scene.intersect(ray)
+    }
+
+    pub fn intersect_batch(&self, rays: &[Ray]) -> Vec<Option<(ObjectId, Hit)>> {
+        rays.iter().map(|ray| self.intersect(ray)).collect()
+    }
+
+    /// Returns whether anything blocks `ray` within `max_distance` of its origin.
+    pub fn occluded(&self, ray: &Ray, max_distance: f32) -> bool {
+        self.scene.occluded(ray, max_distance)
+    }
+
+    /// Occlusion test for a batch of rays, each paired with its maximum distance.
+    pub fn occluded_batch(&self, rays: &[(Ray, f32)]) -> Vec<bool> {
+        rays.iter()
+            .map(|(ray, max_distance)| self.occluded(ray, *max_distance))
+            .collect()
+    }
+
+    /// Traces the ray through pixel `(x, y)` of the current view for inspection.
+    pub fn inspect(&self, x: u32, y: u32) -> Option<RayTrace> {
+        self.scene.camera.inspect(&self.scene, x, y)
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.scene.camera.resize(width, height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use glam::{vec3, Vec3};
+
+    use super::*;
+    use crate::{camera::CameraDescriptor, surface::SurfaceMaterial};
+
+    fn raytracer() -> Raytracer {
+        let scene = Scene::builder()
+            .camera(CameraDescriptor {
+                width: 8,
+                height: 6,
+                vfov: 50.,
+                eye_pointer: Vec3::ZERO,
+                look_pointer: Vec3::NEG_Z,
+                up_pointer: Vec3::Y,
+                ..Default::default()
+            })
+            .point_light(vec3(0., 5., 0.), 1.)
+            .sphere(vec3(0., 0., -5.), 1., SurfaceMaterial::default())
+            .build()
+            .unwrap();
+        Raytracer::with_scene(scene)
+    }
+
+    #[test]
+    fn progress_can_cancel_the_render() {
+        let mut raytracer = raytracer();
+
+        let mut reports = 0;
+        let frame = raytracer.render_with_progress(|progress| {
+            reports += 1;
+            if progress.rows_done == 2 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert!(frame.is_none());
+        assert_eq!(reports, 2);
+        assert_eq!(raytracer.stats().primary_rays, 2 * 8);
+
+        // the next render starts over and runs to the end
+        let frame = raytracer.render_with_progress(|_| ControlFlow::Continue(()));
+        assert_eq!(frame.unwrap(), raytracer.render());
+    }
+
+    #[test]
+    fn progress_runs_from_start_to_finish() {
+        let mut raytracer = raytracer();
+
+        let mut reports = Vec::new();
+        raytracer
+            .render_with_progress(|progress| {
+                reports.push(progress);
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+
+        assert_eq!(reports.len(), 6);
+        assert!(reports
+            .windows(2)
+            .all(|pair| pair[0].fraction() < pair[1].fraction()));
+        let last = reports.last().unwrap();
+        assert_eq!(last.fraction(), 1.);
+        assert_eq!(last.remaining(), Duration::ZERO);
     }
 }
@@ -0,0 +1,336 @@
+use std::path::Path;
+
+use eyre::{bail, eyre, Result};
+use glam::{Affine3A, Vec3};
+
+use crate::{
+    animation::Animation,
+    camera::{Camera, CameraDescriptor},
+    import::{
+        gltf::{self, GltfOptions},
+        obj::{self, ObjOptions},
+        ply, stl, MeshOptions,
+    },
+    light::Light,
+    material::Material,
+    medium::{Bounds, Medium, Volume},
+    object::{sphere::Sphere, triangle::Triangle, Object},
+    object_store::{MaterialId, ObjectStore},
+};
+
+/// A scene is the contents of an [`ObjectStore`]; this name reads better when assembling one in
+/// code.
+pub type Scene = ObjectStore;
+
+impl ObjectStore {
+    pub fn builder() -> SceneBuilder {
+        SceneBuilder::default()
+    }
+}
+
+/// Fluent construction of a [`Scene`]. Every step validates its input; the first error is kept
+/// and returned by [`SceneBuilder::build`], later steps are skipped.
+#[derive(Default)]
+pub struct SceneBuilder {
+    store: ObjectStore,
+    camera: Option<CameraDescriptor>,
+    error: Option<eyre::Report>,
+}
+
+impl SceneBuilder {
+    pub fn camera(self, camera: CameraDescriptor) -> Self {
+        self.step(|builder| {
+            if camera.width == 0 || camera.height == 0 {
+                bail!(
+                    "camera image size {}x{} is empty",
+                    camera.width,
+                    camera.height
+                );
+            }
+            if !(camera.vfov > 0. && camera.vfov < 180.) {
+                bail!("camera field of view of {}° is out of range", camera.vfov);
+            }
+
+            let gaze = camera.look_pointer - camera.eye_pointer;
+            if gaze.length() <= f32::EPSILON {
+                bail!("camera eye and look points coincide");
+            }
+            if is_parallel(gaze, camera.up_pointer) {
+                bail!(
+                    "camera up vector {} is zero or parallel to the view direction",
+                    camera.up_pointer
+                );
+            }
+
+            builder.camera = Some(camera);
+            Ok(())
+        })
+    }
+
+    pub fn point_light(self, position: Vec3, brightness: f32) -> Self {
+        self.step(|builder| {
+            if !(position.is_finite() && brightness.is_finite() && brightness >= 0.) {
+                bail!("invalid light at {position} with brightness {brightness}");
+            }
+
+            builder.store.add_light(Light::new(position, brightness));
+            Ok(())
+        })
+    }
+
+    /// Fills the whole scene with `medium`, as homogeneous fog.
+    pub fn fog(self, medium: Medium) -> Self {
+        self.volume(medium, Bounds::Everywhere)
+    }
+
+    /// Fills the region `bounds` with `medium`.
+    pub fn volume(self, medium: Medium, bounds: Bounds) -> Self {
+        self.step(|builder| {
+            let coefficients = [medium.absorption, medium.scattering].map(Vec3::from);
+            if !coefficients
+                .iter()
+                .all(|c| c.is_finite() && c.cmpge(Vec3::ZERO).all())
+            {
+                bail!("medium has negative or invalid coefficients");
+            }
+            if !(medium.asymmetry > -1. && medium.asymmetry < 1.) {
+                bail!("medium asymmetry {} is out of range", medium.asymmetry);
+            }
+
+            match bounds {
+                Bounds::Everywhere => {}
+                Bounds::Sphere { center, radius } => {
+                    if !(radius.is_finite() && radius > 0.) {
+                        bail!("volume sphere at {center} has invalid radius {radius}");
+                    }
+                }
+                Bounds::Box(aabb) => {
+                    if !aabb.min.cmplt(aabb.max).all() {
+                        bail!("volume box from {} to {} is empty", aabb.min, aabb.max);
+                    }
+                }
+            }
+
+            builder.store.add_volume(Volume { medium, bounds });
+            Ok(())
+        })
+    }
+
+    pub fn sphere(self, center: Vec3, radius: f32, material: impl Into<Material>) -> Self {
+        self.step(|builder| {
+            if !(radius.is_finite() && radius > 0.) {
+                bail!("sphere at {center} has invalid radius {radius}");
+            }
+
+            let material = builder.store.add_material("sphere", material);
+            builder
+                .store
+                .add_object(Sphere::new(center, radius, material));
+            Ok(())
+        })
+    }
+
+    pub fn triangle(self, a: Vec3, b: Vec3, c: Vec3, material: impl Into<Material>) -> Self {
+        self.step(|builder| {
+            if is_parallel(b - a, c - a) {
+                bail!("triangle {a}, {b}, {c} is degenerate");
+            }
+
+            let material = builder.store.add_material("triangle", material);
+            builder
+                .store
+                .add_object(Triangle::from_vertices(a, b, c, material));
+            Ok(())
+        })
+    }
+
+    /// Adds an arbitrary object, built by `object` from the handle of `material`.
+    pub fn object<O: Into<Object>>(
+        self,
+        material: impl Into<Material>,
+        object: impl FnOnce(MaterialId) -> O,
+    ) -> Self {
+        self.step(|builder| {
+            let material = builder.store.add_material("object", material);
+            builder.store.add_object(object(material));
+            Ok(())
+        })
+    }
+
+    /// Loads every model of a Wavefront OBJ file along with its materials, placing the models
+    /// with `transform`.
+    pub fn mesh_from_obj(self, path: impl AsRef<Path>, transform: Affine3A) -> Self {
+        self.mesh_from_obj_with(
+            path,
+            &ObjOptions {
+                transform,
+                ..Default::default()
+            },
+        )
+    }
+
+    pub fn mesh_from_obj_with(self, path: impl AsRef<Path>, options: &ObjOptions) -> Self {
+        self.step(|builder| obj::load(&mut builder.store, path, options).map(|_| ()))
+    }
+
+    /// Loads the mesh of an ASCII or binary PLY file, shaded with its vertex colors if it has any.
+    pub fn mesh_from_ply(self, path: impl AsRef<Path>, transform: Affine3A) -> Self {
+        self.mesh_from_ply_with(
+            path,
+            &MeshOptions {
+                transform,
+                ..Default::default()
+            },
+        )
+    }
+
+    pub fn mesh_from_ply_with(self, path: impl AsRef<Path>, options: &MeshOptions) -> Self {
+        self.step(|builder| ply::load(&mut builder.store, path, options).map(|_| ()))
+    }
+
+    /// Loads the mesh of an ASCII or binary STL file.
+    pub fn mesh_from_stl(self, path: impl AsRef<Path>, transform: Affine3A) -> Self {
+        self.mesh_from_stl_with(
+            path,
+            &MeshOptions {
+                transform,
+                ..Default::default()
+            },
+        )
+    }
+
+    pub fn mesh_from_stl_with(self, path: impl AsRef<Path>, options: &MeshOptions) -> Self {
+        self.step(|builder| stl::load(&mut builder.store, path, options).map(|_| ()))
+    }
+
+    /// Loads the default scene of a glTF or GLB file with its materials and lights. The first
+    /// camera of the file is used unless the scene already has one.
+    pub fn scene_from_gltf(self, path: impl AsRef<Path>) -> Self {
+        self.scene_from_gltf_with(path, &GltfOptions::default())
+    }
+
+    pub fn scene_from_gltf_with(self, path: impl AsRef<Path>, options: &GltfOptions) -> Self {
+        let mut cameras = Vec::new();
+        let builder = self.step(|builder| {
+            cameras = gltf::load(&mut builder.store, path, options)?.cameras;
+            Ok(())
+        });
+
+        match cameras.into_iter().next() {
+            Some(camera) if builder.camera.is_none() => builder.camera(camera),
+            _ => builder,
+        }
+    }
+
+    pub fn animation(mut self, animation: Animation) -> Self {
+        self.store.animation = animation;
+        self
+    }
+
+    pub fn build(self) -> Result<Scene> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        let mut store = self.store;
+        let camera = self.camera.ok_or_else(|| eyre!("scene has no camera"))?;
+        store.camera = Camera::new(&camera);
+        store.camera.calculate_first_ray();
+
+        Ok(store)
+    }
+
+    fn step(mut self, step: impl FnOnce(&mut Self) -> Result<()>) -> Self {
+        if self.error.is_none() {
+            if let Err(error) = step(&mut self) {
+                self.error = Some(error);
+            }
+        }
+        self
+    }
+}
+
+/// Returns whether `a` and `b` are parallel or either is zero.
+fn is_parallel(a: Vec3, b: Vec3) -> bool {
+    a.cross(b).length() <= f32::EPSILON * a.length() * b.length()
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::vec3;
+
+    use super::*;
+    use crate::{aabb::Aabb, color::Color, ray::Ray, surface::SurfaceMaterial};
+
+    fn camera() -> CameraDescriptor {
+        CameraDescriptor {
+            width: 4,
+            height: 4,
+            vfov: 50.,
+            eye_pointer: Vec3::ZERO,
+            look_pointer: Vec3::NEG_Z,
+            up_pointer: Vec3::Y,
+            max_level: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn builds_scene() {
+        let scene = Scene::builder()
+            .camera(camera())
+            .point_light(vec3(0., 5., 0.), 1.)
+            .sphere(vec3(0., 0., -5.), 1., SurfaceMaterial::default())
+            .triangle(
+                vec3(-1., -1., -10.),
+                vec3(1., -1., -10.),
+                vec3(0., 1., -10.),
+                SurfaceMaterial::default(),
+            )
+            .build()
+            .unwrap();
+
+        assert_eq!(scene.objects().len(), 2);
+        let (_, hit) = scene
+            .intersect(&Ray::new(Vec3::ZERO, Vec3::NEG_Z, 0.))
+            .unwrap();
+        assert_eq!(hit.distance, 4.);
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        let zero_radius =
+            Scene::builder()
+                .camera(camera())
+                .sphere(Vec3::ZERO, 0., SurfaceMaterial::default());
+        assert!(zero_radius.build().is_err());
+
+        let degenerate = Scene::builder().camera(camera()).triangle(
+            Vec3::ZERO,
+            Vec3::X,
+            Vec3::X * 2.,
+            SurfaceMaterial::default(),
+        );
+        assert!(degenerate.build().is_err());
+
+        let no_up = Scene::builder().camera(CameraDescriptor {
+            up_pointer: Vec3::ZERO,
+            ..camera()
+        });
+        assert!(no_up.build().is_err());
+
+        let dark_fog = Scene::builder().camera(camera()).fog(Medium {
+            absorption: Color::new(-1., 0., 0.),
+            ..Default::default()
+        });
+        assert!(dark_fog.build().is_err());
+
+        let flat_box = Scene::builder().camera(camera()).volume(
+            Medium::default(),
+            Bounds::Box(Aabb::new(Vec3::ZERO, vec3(1., 0., 1.))),
+        );
+        assert!(flat_box.build().is_err());
+
+        assert!(Scene::builder().build().is_err());
+    }
+}
@@ -0,0 +1,212 @@
+//! Closed-form polynomial root finding, following Jochen Schwarze's solvers from Graphics Gems.
+//! Coefficients are given in ascending order of degree.
+
+use std::{f64::consts::PI, ops::Deref};
+
+const EPSILON: f64 = 1e-9;
+
+/// Real roots of a polynomial of degree four or less, in no particular order.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Roots {
+    values: [f64; 4],
+    len: usize,
+}
+
+impl Roots {
+    fn push(&mut self, value: f64) {
+        self.values[self.len] = value;
+        self.len += 1;
+    }
+
+    fn shift(mut self, offset: f64) -> Self {
+        for value in &mut self.values[..self.len] {
+            *value += offset;
+        }
+        self
+    }
+}
+
+impl Deref for Roots {
+    type Target = [f64];
+
+    fn deref(&self) -> &[f64] {
+        &self.values[..self.len]
+    }
+}
+
+fn is_zero(x: f64) -> bool {
+    x.abs() < EPSILON
+}
+
+/// Solves `c[2] x² + c[1] x + c[0] = 0`, degrading to the linear case if `c[2]` vanishes.
+pub fn solve_quadratic(c: [f64; 3]) -> Roots {
+    let mut roots = Roots::default();
+    let [c0, c1, c2] = c;
+
+    if is_zero(c2) {
+        if !is_zero(c1) {
+            roots.push(-c0 / c1);
+        }
+        return roots;
+    }
+
+    let discriminant = c1 * c1 - 4. * c2 * c0;
+    if discriminant < 0. {
+        return roots;
+    }
+
+    // avoids cancellation between `-c1` and the square root
+    let q = -0.5 * (c1 + c1.signum() * discriminant.sqrt());
+    if is_zero(q) {
+        roots.push(0.);
+    } else {
+        roots.push(q / c2);
+        roots.push(c0 / q);
+    }
+    roots
+}
+
+/// Solves `c[3] x³ + c[2] x² + c[1] x + c[0] = 0` for `c[3] != 0`.
+pub fn solve_cubic(c: [f64; 4]) -> Roots {
+    let mut roots = Roots::default();
+
+    let a = c[2] / c[3];
+    let b = c[1] / c[3];
+    let c = c[0] / c[3];
+
+    // substitute x = y - a/3 to eliminate the quadratic term: y³ + 3p y + 2q = 0
+    let sq_a = a * a;
+    let p = (-sq_a / 3. + b) / 3.;
+    let q = (2. / 27. * a * sq_a - a * b / 3. + c) / 2.;
+
+    let cb_p = p * p * p;
+    let d = q * q + cb_p;
+
+    if is_zero(d) {
+        if is_zero(q) {
+            roots.push(0.);
+        } else {
+            let u = (-q).cbrt();
+            roots.push(2. * u);
+            roots.push(-u);
+        }
+    } else if d < 0. {
+        let phi = (-q / (-cb_p).sqrt()).clamp(-1., 1.).acos() / 3.;
+        let t = 2. * (-p).sqrt();
+        roots.push(t * phi.cos());
+        roots.push(-t * (phi + PI / 3.).cos());
+        roots.push(-t * (phi - PI / 3.).cos());
+    } else {
+        let sqrt_d = d.sqrt();
+        roots.push((sqrt_d - q).cbrt() - (sqrt_d + q).cbrt());
+    }
+
+    roots.shift(-a / 3.)
+}
+
+/// Solves `c[4] x⁴ + c[3] x³ + c[2] x² + c[1] x + c[0] = 0` for `c[4] != 0`. The roots are
+/// polished with a Newton step on the original polynomial.
+pub fn solve_quartic(coefficients: [f64; 5]) -> Roots {
+    let mut roots = Roots::default();
+    let c = coefficients;
+
+    let a = c[3] / c[4];
+    let b = c[2] / c[4];
+    let cc = c[1] / c[4];
+    let d = c[0] / c[4];
+
+    // substitute x = y - a/4 to eliminate the cubic term: y⁴ + p y² + q y + r = 0
+    let sq_a = a * a;
+    let p = -3. / 8. * sq_a + b;
+    let q = sq_a * a / 8. - a * b / 2. + cc;
+    let r = -3. / 256. * sq_a * sq_a + sq_a * b / 16. - a * cc / 4. + d;
+
+    if is_zero(r) {
+        // y (y³ + p y + q) = 0
+        for root in solve_cubic([q, p, 0., 1.]).iter() {
+            roots.push(*root);
+        }
+        roots.push(0.);
+    } else {
+        // take one root of the resolvent cubic to factor into two quadratics
+        let z = solve_cubic([r * p / 2. - q * q / 8., -r, -p / 2., 1.])[0];
+
+        let u = z * z - r;
+        let v = 2. * z - p;
+
+        let u = if is_zero(u) {
+            0.
+        } else if u > 0. {
+            u.sqrt()
+        } else {
+            return roots;
+        };
+        let v = if is_zero(v) {
+            0.
+        } else if v > 0. {
+            v.sqrt()
+        } else {
+            return roots;
+        };
+
+        let v = if q < 0. { -v } else { v };
+        for root in solve_quadratic([z - u, v, 1.])
+            .iter()
+            .chain(solve_quadratic([z + u, -v, 1.]).iter())
+        {
+            roots.push(*root);
+        }
+    }
+
+    let mut roots = roots.shift(-a / 4.);
+    for root in &mut roots.values[..roots.len] {
+        let x = *root;
+        let f = (((c[4] * x + c[3]) * x + c[2]) * x + c[1]) * x + c[0];
+        let df = ((4. * c[4] * x + 3. * c[3]) * x + 2. * c[2]) * x + c[1];
+        if df != 0. {
+            *root = x - f / df;
+        }
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(roots: Roots) -> Vec<f64> {
+        let mut roots = roots.to_vec();
+        roots.sort_by(f64::total_cmp);
+        roots
+    }
+
+    fn assert_roots(actual: Roots, expected: &[f64]) {
+        let actual = sorted(actual);
+        assert_eq!(actual.len(), expected.len(), "{actual:?}");
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-6, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn quadratic() {
+        // (x - 1)(x - 3)
+        assert_roots(solve_quadratic([3., -4., 1.]), &[1., 3.]);
+        assert_roots(solve_quadratic([1., 0., 1.]), &[]);
+        assert_roots(solve_quadratic([-2., 1., 0.]), &[2.]);
+    }
+
+    #[test]
+    fn cubic() {
+        // (x + 1)(x - 2)(x - 4)
+        assert_roots(solve_cubic([8., 2., -5., 1.]), &[-1., 2., 4.]);
+    }
+
+    #[test]
+    fn quartic() {
+        // (x + 2)(x - 1)(x - 3)(x - 5)
+        assert_roots(solve_quartic([-30., 31., 5., -7., 1.]), &[-2., 1., 3., 5.]);
+        // (x² + 1)(x - 1)(x - 2)
+        assert_roots(solve_quartic([2., -3., 3., -3., 1.]), &[1., 2.]);
+    }
+}
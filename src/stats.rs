@@ -0,0 +1,193 @@
+use std::{cell::Cell, fmt, time::Duration};
+
+/// Ray counts and timings of the last frame rendered by a [`Camera`](crate::camera::Camera).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenderStats {
+    pub width: u32,
+    pub height: u32,
+    pub primary_rays: u64,
+    /// Reflected and transmitted rays.
+    pub secondary_rays: u64,
+    /// Rays towards lights, including those gathering light scattered by volumes, and ambient
+    /// occlusion probes.
+    pub shadow_rays: u64,
+    /// Tests of a ray against one of the scene's top level objects.
+    pub intersection_tests: u64,
+    /// Tests of a ray against the bounds of a BVH node, in groups and meshes.
+    pub node_tests: u64,
+    /// Tests of a ray against a triangle of a mesh.
+    pub triangle_tests: u64,
+    /// Primary rays that ran out of their ray budget and skipped some of their secondary rays.
+    pub exhausted_budgets: u64,
+    /// Wall clock time of each phase of the render, in order. Shading, shadow rays and volumes
+    /// are interleaved for every pixel and all part of the tracing phase; timing them apart
+    /// would take a clock read around every shadow ray, which costs about as much as the ray.
+    pub phases: Vec<(&'static str, Duration)>,
+}
+
+impl RenderStats {
+    pub fn rays(&self) -> u64 {
+        self.primary_rays + self.secondary_rays + self.shadow_rays
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.phases.iter().map(|(_, duration)| *duration).sum()
+    }
+
+    pub fn rays_per_second(&self) -> f64 {
+        self.rays() as f64 / self.duration().as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+impl fmt::Display for RenderStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "rendered {}x{} in {:?}",
+            self.width,
+            self.height,
+            self.duration()
+        )?;
+        writeln!(
+            f,
+            "  rays: {} primary, {} secondary, {} shadow ({:.2}M rays/s)",
+            self.primary_rays,
+            self.secondary_rays,
+            self.shadow_rays,
+            self.rays_per_second() / 1e6
+        )?;
+        writeln!(
+            f,
+            "  intersection tests: {} objects, {} BVH nodes, {} triangles",
+            self.intersection_tests, self.node_tests, self.triangle_tests
+        )?;
+        if self.exhausted_budgets > 0 {
+            writeln!(
+                f,
+                "  ray budget exhausted by {} primary rays",
+                self.exhausted_budgets
+            )?;
+        }
+
+        write!(f, "  phases:")?;
+        for (i, (phase, duration)) in self.phases.iter().enumerate() {
+            let separator = if i == 0 { "" } else { "," };
+            write!(f, "{separator} {phase} {duration:?}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Ray counters updated through shared references while tracing, along with the budget of
+/// secondary rays left to the current primary ray.
+#[derive(Debug, Default)]
+pub(crate) struct RayCounters {
+    primary: Cell<u64>,
+    secondary: Cell<u64>,
+    shadow: Cell<u64>,
+    exhausted: Cell<u64>,
+    /// Rays the current primary ray may still spawn, `None` if it has no budget.
+    remaining: Cell<Option<u32>>,
+    /// Whether the current primary ray has been denied a ray.
+    denied: Cell<bool>,
+}
+
+impl RayCounters {
+    /// Counts a primary ray, which may spawn up to `budget` further rays.
+    pub fn primary(&self, budget: Option<u32>) {
+        add(&self.primary, 1);
+        self.remaining.set(budget);
+        self.denied.set(false);
+    }
+
+    /// Counts a reflected or transmitted ray. Returns `false` if the budget is used up and the
+    /// ray must not be traced.
+    pub fn secondary(&self) -> bool {
+        self.spend(&self.secondary)
+    }
+
+    /// Counts a shadow ray or occlusion probe, like [`RayCounters::secondary`].
+    pub fn shadow(&self) -> bool {
+        self.spend(&self.shadow)
+    }
+
+    fn spend(&self, counter: &Cell<u64>) -> bool {
+        match self.remaining.get() {
+            Some(0) => {
+                if !self.denied.replace(true) {
+                    add(&self.exhausted, 1);
+                }
+                false
+            }
+            remaining => {
+                self.remaining.set(remaining.map(|n| n - 1));
+                add(counter, 1);
+                true
+            }
+        }
+    }
+
+    /// Fills in the ray counts of `stats`.
+    pub fn report(&self, stats: &mut RenderStats) {
+        stats.primary_rays = self.primary.get();
+        stats.secondary_rays = self.secondary.get();
+        stats.shadow_rays = self.shadow.get();
+        stats.exhausted_budgets = self.exhausted.get();
+    }
+}
+
+fn add(counter: &Cell<u64>, n: u64) {
+    counter.set(counter.get() + n);
+}
+
+thread_local! {
+    // Counted by groups and meshes, which don't know the scene they are traced in.
+    static NODE_TESTS: Cell<u64> = const { Cell::new(0) };
+    static TRIANGLE_TESTS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Counts `n` tests of a ray against the bounds of a BVH node on this thread.
+pub(crate) fn count_node_tests(n: u64) {
+    NODE_TESTS.with(|tests| add(tests, n));
+}
+
+/// Counts `n` ray-triangle tests on this thread.
+pub(crate) fn count_triangle_tests(n: u64) {
+    TRIANGLE_TESTS.with(|tests| add(tests, n));
+}
+
+/// Returns the BVH node and triangle tests on this thread since the last call and restarts the
+/// counts.
+pub(crate) fn take_traversal_tests() -> (u64, u64) {
+    (NODE_TESTS.with(Cell::take), TRIANGLE_TESTS.with(Cell::take))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_limits_rays_per_primary_ray() {
+        let counters = RayCounters::default();
+
+        counters.primary(Some(2));
+        assert!(counters.secondary());
+        assert!(counters.shadow());
+        assert!(!counters.shadow());
+        assert!(!counters.secondary());
+
+        // the budget is renewed for every primary ray
+        counters.primary(Some(2));
+        assert!(counters.shadow());
+
+        counters.primary(None);
+        assert!((0..100).all(|_| counters.secondary()));
+
+        let mut stats = RenderStats::default();
+        counters.report(&mut stats);
+        assert_eq!(stats.primary_rays, 3);
+        assert_eq!(stats.secondary_rays, 101);
+        assert_eq!(stats.shadow_rays, 2);
+        assert_eq!(stats.exhausted_budgets, 1);
+    }
+}
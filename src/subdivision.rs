@@ -0,0 +1,411 @@
+use std::{collections::HashMap, str::FromStr};
+
+use eyre::{bail, Report, Result, WrapErr};
+use glam::Vec3;
+
+use crate::{object::mesh::Mesh, object_store::MaterialId};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    /// Loop subdivision; polygons are fan-triangulated before the first step.
+    Loop,
+    /// Catmull–Clark subdivision, turning every n-gon into n quads per step.
+    CatmullClark,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Subdivision {
+    pub scheme: Scheme,
+    pub levels: u32,
+}
+
+/// Parses `<scheme>:<levels>` with a scheme of `loop` or `catmull-clark`, such as
+/// `catmull-clark:2`. Without levels, the mesh is subdivided once.
+impl FromStr for Subdivision {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (scheme, levels) = s.split_once(':').unwrap_or((s, "1"));
+        let scheme = match scheme {
+            "loop" => Scheme::Loop,
+            "catmull-clark" => Scheme::CatmullClark,
+            _ => bail!("unknown subdivision scheme `{scheme}`"),
+        };
+        let levels = levels
+            .parse()
+            .wrap_err_with(|| format!("parsing subdivision levels `{levels}`"))?;
+
+        Ok(Subdivision { scheme, levels })
+    }
+}
+
+/// Polygonal control cage. Open boundaries are kept as creases: boundary edges and vertices are
+/// refined using only their neighbours along the boundary, and vertices belonging to a single
+/// face are kept as corners, so flat borders stay in place.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PolygonMesh {
+    pub positions: Vec<Vec3>,
+    pub faces: Vec<Vec<u32>>,
+}
+
+/// Faces and vertices adjacent to an undirected edge, the latter being the vertex following the
+/// edge in each face. Loop subdivision needs them when the faces are triangles.
+#[derive(Default)]
+struct Edge {
+    index: usize,
+    faces: Vec<usize>,
+    opposite: Vec<u32>,
+}
+
+/// Edge connectivity of a polygon mesh, keyed by the ordered vertex pair.
+struct Topology {
+    edges: HashMap<(u32, u32), Edge>,
+    vertex_faces: Vec<Vec<usize>>,
+    vertex_edges: Vec<Vec<(u32, u32)>>,
+}
+
+fn edge_key(a: u32, b: u32) -> (u32, u32) {
+    (a.min(b), a.max(b))
+}
+
+impl Topology {
+    fn new(mesh: &PolygonMesh) -> Self {
+        let mut edges = HashMap::<_, Edge>::new();
+        let mut vertex_faces = vec![Vec::new(); mesh.positions.len()];
+        let mut vertex_edges = vec![Vec::new(); mesh.positions.len()];
+
+        for (f, face) in mesh.faces.iter().enumerate() {
+            for (i, &a) in face.iter().enumerate() {
+                let b = face[(i + 1) % face.len()];
+                let c = face[(i + 2) % face.len()];
+                vertex_faces[a as usize].push(f);
+
+                let key = edge_key(a, b);
+                let next_index = edges.len();
+                let edge = edges.entry(key).or_insert_with(|| {
+                    vertex_edges[a as usize].push(key);
+                    vertex_edges[b as usize].push(key);
+                    Edge {
+                        index: next_index,
+                        ..Default::default()
+                    }
+                });
+                edge.faces.push(f);
+                edge.opposite.push(c);
+            }
+        }
+
+        Topology {
+            edges,
+            vertex_faces,
+            vertex_edges,
+        }
+    }
+
+    fn edge(&self, a: u32, b: u32) -> &Edge {
+        &self.edges[&edge_key(a, b)]
+    }
+
+    /// Neighbours of `vertex` along boundary edges; a regular boundary vertex has exactly two.
+    fn boundary_neighbours(&self, vertex: usize) -> Vec<u32> {
+        self.vertex_edges[vertex]
+            .iter()
+            .filter(|key| self.edges[key].faces.len() != 2)
+            .map(|&(a, b)| if a as usize == vertex { b } else { a })
+            .collect()
+    }
+}
+
+impl PolygonMesh {
+    /// Builds a cage from a flat index list and the vertex count of each face, as found in OBJ
+    /// files loaded without triangulation.
+    pub fn from_arities(positions: Vec<Vec3>, indices: &[u32], arities: &[u32]) -> Self {
+        let mut offset = 0;
+        let faces = arities
+            .iter()
+            .map(|&arity| {
+                let face = indices[offset..offset + arity as usize].to_vec();
+                offset += arity as usize;
+                face
+            })
+            .collect();
+
+        PolygonMesh { positions, faces }
+    }
+
+    pub fn subdivide(self, subdivision: Subdivision) -> Self {
+        (0..subdivision.levels).fold(self, |mesh, _| match subdivision.scheme {
+            Scheme::Loop => mesh.loop_step(),
+            Scheme::CatmullClark => mesh.catmull_clark_step(),
+        })
+    }
+
+    fn catmull_clark_step(&self) -> Self {
+        let topology = Topology::new(self);
+        let vertex_count = self.positions.len();
+        let edge_count = topology.edges.len();
+
+        let face_points = self
+            .faces
+            .iter()
+            .map(|face| {
+                face.iter()
+                    .map(|&i| self.positions[i as usize])
+                    .sum::<Vec3>()
+                    / face.len() as f32
+            })
+            .collect::<Vec<_>>();
+
+        let mut positions = vec![Vec3::ZERO; vertex_count + edge_count + self.faces.len()];
+
+        for (&(a, b), edge) in &topology.edges {
+            let midpoint = (self.positions[a as usize] + self.positions[b as usize]) / 2.;
+            positions[vertex_count + edge.index] = match edge.faces[..] {
+                [f0, f1] => (midpoint + (face_points[f0] + face_points[f1]) / 2.) / 2.,
+                _ => midpoint,
+            };
+        }
+
+        for (vertex, position) in self.positions.iter().enumerate() {
+            let faces = &topology.vertex_faces[vertex];
+            let edges = &topology.vertex_edges[vertex];
+            let boundary = topology.boundary_neighbours(vertex);
+
+            positions[vertex] = match boundary[..] {
+                [] if !faces.is_empty() => {
+                    let n = faces.len() as f32;
+                    let face_average = faces.iter().map(|&f| face_points[f]).sum::<Vec3>() / n;
+                    let edge_average = edges
+                        .iter()
+                        .map(|&(a, b)| {
+                            (self.positions[a as usize] + self.positions[b as usize]) / 2.
+                        })
+                        .sum::<Vec3>()
+                        / edges.len() as f32;
+                    (face_average + 2. * edge_average + (n - 3.) * *position) / n
+                }
+                [a, b] if faces.len() > 1 => {
+                    (6. * *position + self.positions[a as usize] + self.positions[b as usize]) / 8.
+                }
+                // corners, including vertices of a single face, and non-manifold vertices stay
+                // where they are
+                _ => *position,
+            };
+        }
+
+        let mut faces = Vec::with_capacity(self.faces.iter().map(Vec::len).sum());
+        for (f, face) in self.faces.iter().enumerate() {
+            let center = (vertex_count + edge_count + f) as u32;
+            positions[center as usize] = face_points[f];
+
+            for (i, &vertex) in face.iter().enumerate() {
+                let next = face[(i + 1) % face.len()];
+                let previous = face[(i + face.len() - 1) % face.len()];
+                faces.push(vec![
+                    vertex,
+                    (vertex_count + topology.edge(vertex, next).index) as u32,
+                    center,
+                    (vertex_count + topology.edge(previous, vertex).index) as u32,
+                ]);
+            }
+        }
+
+        PolygonMesh { positions, faces }
+    }
+
+    fn loop_step(&self) -> Self {
+        let triangulated = PolygonMesh {
+            positions: self.positions.clone(),
+            faces: self.triangles().into_iter().map(Vec::from).collect(),
+        };
+        let topology = Topology::new(&triangulated);
+        let vertex_count = self.positions.len();
+
+        let mut positions = vec![Vec3::ZERO; vertex_count + topology.edges.len()];
+
+        for (&(a, b), edge) in &topology.edges {
+            let (a, b) = (self.positions[a as usize], self.positions[b as usize]);
+            positions[vertex_count + edge.index] = match edge.opposite[..] {
+                [c, d] => {
+                    (a + b) * 3. / 8.
+                        + (self.positions[c as usize] + self.positions[d as usize]) / 8.
+                }
+                _ => (a + b) / 2.,
+            };
+        }
+
+        for (vertex, position) in self.positions.iter().enumerate() {
+            let faces = &topology.vertex_faces[vertex];
+            let edges = &topology.vertex_edges[vertex];
+            let boundary = topology.boundary_neighbours(vertex);
+
+            positions[vertex] = match boundary[..] {
+                [] if !edges.is_empty() => {
+                    let n = edges.len() as f32;
+                    let beta = if edges.len() == 3 {
+                        3. / 16.
+                    } else {
+                        3. / (8. * n)
+                    };
+                    let neighbours = edges
+                        .iter()
+                        .map(|&(a, b)| self.positions[(a + b) as usize - vertex])
+                        .sum::<Vec3>();
+                    (1. - n * beta) * *position + beta * neighbours
+                }
+                [a, b] if faces.len() > 1 => {
+                    0.75 * *position
+                        + (self.positions[a as usize] + self.positions[b as usize]) / 8.
+                }
+                _ => *position,
+            };
+        }
+
+        let faces = triangulated
+            .faces
+            .iter()
+            .flat_map(|face| {
+                let [a, b, c] = [face[0], face[1], face[2]];
+                let edge = |a, b| (vertex_count + topology.edge(a, b).index) as u32;
+                let (ab, bc, ca) = (edge(a, b), edge(b, c), edge(c, a));
+                [
+                    vec![a, ab, ca],
+                    vec![ab, b, bc],
+                    vec![ca, bc, c],
+                    vec![ab, bc, ca],
+                ]
+            })
+            .collect();
+
+        PolygonMesh { positions, faces }
+    }
+
+    /// Fan-triangulates every face.
+    pub fn triangles(&self) -> Vec<[u32; 3]> {
+        self.faces
+            .iter()
+            .flat_map(|face| {
+                (1..face.len().saturating_sub(1)).map(|i| [face[0], face[i], face[i + 1]])
+            })
+            .collect()
+    }
+
+    /// Area-weighted vertex normals of the triangulated surface.
+    pub fn vertex_normals(&self) -> Vec<Vec3> {
+        let mut normals = vec![Vec3::ZERO; self.positions.len()];
+        for triangle in self.triangles() {
+            let [a, b, c] = triangle.map(|i| self.positions[i as usize]);
+            let normal = (b - a).cross(c - a);
+            for i in triangle {
+                normals[i as usize] += normal;
+            }
+        }
+
+        normals.into_iter().map(Vec3::normalize_or_zero).collect()
+    }
+
+    /// Triangulates the mesh into a smooth shaded [`Mesh`].
+    pub fn into_mesh(self, material: MaterialId) -> Mesh {
+        let triangles = self.triangles();
+        let normals = self.vertex_normals();
+        Mesh::new(self.positions, normals, Vec::new(), triangles, material)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cube() -> PolygonMesh {
+        let positions = (0..8)
+            .map(|i| Vec3::new((i & 1) as f32, (i >> 1 & 1) as f32, (i >> 2 & 1) as f32) * 2. - 1.)
+            .collect();
+        let faces = vec![
+            vec![0, 2, 3, 1],
+            vec![4, 5, 7, 6],
+            vec![0, 1, 5, 4],
+            vec![2, 6, 7, 3],
+            vec![0, 4, 6, 2],
+            vec![1, 3, 7, 5],
+        ];
+
+        PolygonMesh { positions, faces }
+    }
+
+    #[test]
+    fn parses_schemes_and_levels() {
+        assert_eq!(
+            "catmull-clark:2".parse::<Subdivision>().unwrap(),
+            Subdivision {
+                scheme: Scheme::CatmullClark,
+                levels: 2,
+            }
+        );
+        assert_eq!(
+            "loop".parse::<Subdivision>().unwrap(),
+            Subdivision {
+                scheme: Scheme::Loop,
+                levels: 1,
+            }
+        );
+        assert!("bezier:1".parse::<Subdivision>().is_err());
+        assert!("loop:-1".parse::<Subdivision>().is_err());
+    }
+
+    #[test]
+    fn catmull_clark_cube() {
+        let mesh = cube().subdivide(Subdivision {
+            scheme: Scheme::CatmullClark,
+            levels: 1,
+        });
+
+        assert_eq!(mesh.positions.len(), 8 + 12 + 6);
+        assert_eq!(mesh.faces.len(), 24);
+
+        // corners are pulled in towards the limit surface, which lies inside the cage
+        assert!(mesh.positions.iter().all(|p| p.abs().max_element() <= 1.));
+        assert!((mesh.positions[0] - Vec3::splat(-5. / 9.)).length() < 1e-5);
+    }
+
+    #[test]
+    fn loop_tetrahedron() {
+        let mesh = PolygonMesh {
+            positions: vec![
+                Vec3::new(1., 1., 1.),
+                Vec3::new(1., -1., -1.),
+                Vec3::new(-1., 1., -1.),
+                Vec3::new(-1., -1., 1.),
+            ],
+            faces: vec![vec![0, 1, 2], vec![0, 3, 1], vec![0, 2, 3], vec![1, 3, 2]],
+        };
+        let mesh = mesh.subdivide(Subdivision {
+            scheme: Scheme::Loop,
+            levels: 2,
+        });
+
+        assert_eq!(mesh.positions.len(), 4 + 6 + 24);
+        assert_eq!(mesh.faces.len(), 64);
+    }
+
+    #[test]
+    fn open_boundary_stays_flat() {
+        let mesh = PolygonMesh {
+            positions: vec![
+                Vec3::new(0., 0., 0.),
+                Vec3::new(1., 0., 0.),
+                Vec3::new(1., 0., 1.),
+                Vec3::new(0., 0., 1.),
+            ],
+            faces: vec![vec![0, 1, 2, 3]],
+        };
+
+        for scheme in [Scheme::Loop, Scheme::CatmullClark] {
+            let mesh = mesh.clone().subdivide(Subdivision { scheme, levels: 2 });
+            assert!(mesh.positions.iter().all(|p| p.y == 0.));
+            assert!(mesh
+                .vertex_normals()
+                .iter()
+                .all(|n| (*n - Vec3::NEG_Y).length() < 1e-6));
+        }
+    }
+}
@@ -1,4 +1,14 @@
-use crate::color::Color;
+use std::f32::consts::PI;
+
+use glam::{Vec2, Vec3};
+
+use crate::{
+    color::Color,
+    material::{
+        cosine_direction, facing, microfacet::Ggx, reflect, to_world, Bsdf, BsdfSample, Lobe,
+        Scatter,
+    },
+};
 
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub struct SurfaceMaterial {
@@ -8,4 +18,87 @@ pub struct SurfaceMaterial {
     pub specular_power: f32,
     pub reflection: f32,
     pub transparency: f32,
+    /// Perceptual roughness blurring the reflection and transmission; zero keeps them perfectly
+    /// sharp.
+    pub roughness: f32,
+}
+
+/// The camera shades Phong materials with its own Phong model. As a BSDF they are the same
+/// diffuse term and highlight, scaled like other BSDFs, with the ambient color as emission and
+/// mirror reflection and straight transmission as secondary rays, spread over a GGX lobe for rough
+/// surfaces.
+impl Bsdf for SurfaceMaterial {
+    fn eval(&self, normal: Vec3, view: Vec3, light: Vec3) -> Color {
+        let normal = facing(normal, view);
+        let n_l = normal.dot(light);
+        if n_l <= 0. {
+            return Color::default();
+        }
+
+        let highlight = reflect(view, normal)
+            .dot(light)
+            .max(0.)
+            .powf(self.specular_power);
+        (self.diffuse * n_l + self.specular * highlight) * (1. / PI)
+    }
+
+    fn sample(&self, normal: Vec3, view: Vec3, u: Vec3) -> Option<BsdfSample> {
+        let normal = facing(normal, view);
+        let light = to_world(normal, cosine_direction(u.y, u.z));
+
+        let pdf = self.pdf(normal, view, light);
+        (pdf > 0.).then(|| BsdfSample {
+            direction: light,
+            weight: self.eval(normal, view, light) * (1. / pdf),
+            lobe: Lobe::Diffuse,
+        })
+    }
+
+    fn pdf(&self, normal: Vec3, view: Vec3, light: Vec3) -> f32 {
+        facing(normal, view).dot(light).max(0.) / PI
+    }
+
+    fn scatter(&self, normal: Vec3, view: Vec3, u: Vec2) -> Scatter {
+        let white = Color::new(1., 1., 1.);
+        let normal = facing(normal, view);
+
+        // facets reflecting below the horizon are hidden by others; the mean normal stands in,
+        // so that no direction is rejected and the lobe keeps its constant weight
+        let (facet, lobe) = if self.roughness > 0. {
+            let facet = Ggx::new(self.roughness, Vec3::ZERO).sample_normal(normal, u);
+            let facet = Some(facet)
+                .filter(|facet| reflect(view, *facet).dot(normal) >= 0.)
+                .unwrap_or(normal);
+            (facet, Lobe::Glossy)
+        } else {
+            (normal, Lobe::Specular)
+        };
+
+        // straight transmission continues the reflection on the far side of the surface
+        let reflected = reflect(view, facet);
+        let transmitted = reflected - 2. * reflected.dot(normal) * normal;
+
+        Scatter {
+            reflection: (self.reflection > 0. && reflected.dot(normal) >= 0.).then(|| BsdfSample {
+                direction: reflected,
+                weight: white * self.reflection,
+                lobe,
+            }),
+            transmission: (self.transparency > 0. && transmitted.dot(normal) <= 0.).then(|| {
+                BsdfSample {
+                    direction: transmitted,
+                    weight: white * self.transparency,
+                    lobe,
+                }
+            }),
+        }
+    }
+
+    fn glossy(&self) -> bool {
+        self.roughness > 0.
+    }
+
+    fn emission(&self) -> Color {
+        self.ambient
+    }
 }